@@ -0,0 +1,244 @@
+//! Support for building `global_state_update` entries which change the auction's state.
+use std::collections::BTreeMap;
+
+use num_rational::Ratio;
+use thiserror::Error;
+
+use casper_types::{
+    bytesrepr::ToBytes,
+    contracts::NamedKeys,
+    system::{
+        auction::{
+            Bid, AUCTION_DELAY_KEY, LOCKED_FUNDS_PERIOD_KEY, UNBONDING_DELAY_KEY,
+            VALIDATOR_SLOTS_KEY,
+        },
+        mint::ROUND_SEIGNIORAGE_RATE_KEY,
+    },
+    CLTyped, CLValue, Key, StoredValue, U512,
+};
+
+/// Errors which can occur while building an [`AuctionStateUpdate`].
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum AuctionStateUpdateError {
+    /// The system contract's named keys don't contain the given key.
+    #[error("Missing named key: {0}")]
+    MissingNamedKey(String),
+    /// The value for the given named key couldn't be converted to a `CLValue`.
+    #[error("Unable to convert value for {0}")]
+    CLValue(String),
+}
+
+/// Builds the `global_state_update` entries for common changes to the auction's state.
+///
+/// The keys under which the auction and mint store their config values are looked up in the
+/// named keys of the respective contracts, so callers never hand-encode a `Key` or
+/// `StoredValue`.
+#[derive(Debug)]
+pub struct AuctionStateUpdate<'a> {
+    auction_named_keys: &'a NamedKeys,
+    mint_named_keys: &'a NamedKeys,
+    entries: BTreeMap<Key, StoredValue>,
+}
+
+impl<'a> AuctionStateUpdate<'a> {
+    /// Creates an empty update, resolving config values against the given named keys of the
+    /// auction and mint contracts.
+    pub fn new(auction_named_keys: &'a NamedKeys, mint_named_keys: &'a NamedKeys) -> Self {
+        AuctionStateUpdate {
+            auction_named_keys,
+            mint_named_keys,
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Seeds the given bid, replacing any existing bid of its validator.
+    pub fn with_bid(mut self, bid: Bid) -> Self {
+        let key = Key::Bid(bid.validator_public_key().to_account_hash());
+        self.entries.insert(key, StoredValue::Bid(Box::new(bid)));
+        self
+    }
+
+    /// Overrides the number of validator slots.
+    pub fn with_validator_slots(
+        self,
+        validator_slots: u32,
+    ) -> Result<Self, AuctionStateUpdateError> {
+        self.with_auction_value(VALIDATOR_SLOTS_KEY, validator_slots)
+    }
+
+    /// Overrides the auction delay.
+    pub fn with_auction_delay(self, auction_delay: u64) -> Result<Self, AuctionStateUpdateError> {
+        self.with_auction_value(AUCTION_DELAY_KEY, auction_delay)
+    }
+
+    /// Overrides the locked funds period.
+    pub fn with_locked_funds_period_millis(
+        self,
+        locked_funds_period_millis: u64,
+    ) -> Result<Self, AuctionStateUpdateError> {
+        self.with_auction_value(LOCKED_FUNDS_PERIOD_KEY, locked_funds_period_millis)
+    }
+
+    /// Overrides the unbonding delay.
+    pub fn with_unbonding_delay(
+        self,
+        unbonding_delay: u64,
+    ) -> Result<Self, AuctionStateUpdateError> {
+        self.with_auction_value(UNBONDING_DELAY_KEY, unbonding_delay)
+    }
+
+    /// Overrides the round seigniorage rate.
+    pub fn with_round_seigniorage_rate(
+        mut self,
+        round_seigniorage_rate: Ratio<u64>,
+    ) -> Result<Self, AuctionStateUpdateError> {
+        let round_seigniorage_rate: Ratio<U512> = {
+            let (numer, denom) = round_seigniorage_rate.into();
+            Ratio::new(numer.into(), denom.into())
+        };
+        let (key, value) = named_entry(
+            self.mint_named_keys,
+            ROUND_SEIGNIORAGE_RATE_KEY,
+            round_seigniorage_rate,
+        )?;
+        self.entries.insert(key, value);
+        Ok(self)
+    }
+
+    /// Returns the entries, ready to be merged into an upgrade's `global_state_update`.
+    pub fn build(self) -> BTreeMap<Key, StoredValue> {
+        self.entries
+    }
+
+    fn with_auction_value<T: CLTyped + ToBytes>(
+        mut self,
+        name: &str,
+        value: T,
+    ) -> Result<Self, AuctionStateUpdateError> {
+        let (key, value) = named_entry(self.auction_named_keys, name, value)?;
+        self.entries.insert(key, value);
+        Ok(self)
+    }
+}
+
+/// Returns the entry writing `value` under the key named `name`.
+fn named_entry<T: CLTyped + ToBytes>(
+    named_keys: &NamedKeys,
+    name: &str,
+    value: T,
+) -> Result<(Key, StoredValue), AuctionStateUpdateError> {
+    let key = named_keys
+        .get(name)
+        .copied()
+        .ok_or_else(|| AuctionStateUpdateError::MissingNamedKey(name.to_string()))?;
+    let cl_value =
+        CLValue::from_t(value).map_err(|_| AuctionStateUpdateError::CLValue(name.to_string()))?;
+    Ok((key, StoredValue::CLValue(cl_value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{bytesrepr::FromBytes, AccessRights, PublicKey, SecretKey, URef};
+
+    use super::*;
+
+    fn named_keys(names: &[&str]) -> NamedKeys {
+        names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| {
+                let uref = URef::new([index as u8; 32], AccessRights::READ_ADD_WRITE);
+                (name.to_string(), Key::URef(uref))
+            })
+            .collect()
+    }
+
+    fn cl_value_under<T: CLTyped + FromBytes>(
+        entries: &BTreeMap<Key, StoredValue>,
+        named_keys: &NamedKeys,
+        name: &str,
+    ) -> T {
+        match &entries[&named_keys[name]] {
+            StoredValue::CLValue(cl_value) => cl_value.clone().into_t().unwrap(),
+            other => panic!("unexpected stored value {:?}", other),
+        }
+    }
+
+    #[test]
+    fn entries_should_deserialize_to_the_intended_values() {
+        let auction_named_keys = named_keys(&[
+            VALIDATOR_SLOTS_KEY,
+            AUCTION_DELAY_KEY,
+            LOCKED_FUNDS_PERIOD_KEY,
+            UNBONDING_DELAY_KEY,
+        ]);
+        let mint_named_keys = named_keys(&[ROUND_SEIGNIORAGE_RATE_KEY]);
+        let public_key = PublicKey::from(
+            &SecretKey::ed25519_from_bytes([42; SecretKey::ED25519_LENGTH]).unwrap(),
+        );
+        let bonding_purse = URef::new([9; 32], AccessRights::READ_ADD_WRITE);
+        let bid = Bid::unlocked(public_key.clone(), bonding_purse, U512::from(1_000), 10);
+
+        let entries = AuctionStateUpdate::new(&auction_named_keys, &mint_named_keys)
+            .with_bid(bid.clone())
+            .with_validator_slots(7)
+            .unwrap()
+            .with_auction_delay(3)
+            .unwrap()
+            .with_locked_funds_period_millis(1_000)
+            .unwrap()
+            .with_unbonding_delay(14)
+            .unwrap()
+            .with_round_seigniorage_rate(Ratio::new(1, 100))
+            .unwrap()
+            .build();
+
+        assert_eq!(entries.len(), 6);
+        assert_eq!(
+            entries[&Key::Bid(public_key.to_account_hash())],
+            StoredValue::Bid(Box::new(bid))
+        );
+        assert_eq!(
+            cl_value_under::<u32>(&entries, &auction_named_keys, VALIDATOR_SLOTS_KEY),
+            7
+        );
+        assert_eq!(
+            cl_value_under::<u64>(&entries, &auction_named_keys, AUCTION_DELAY_KEY),
+            3
+        );
+        assert_eq!(
+            cl_value_under::<u64>(&entries, &auction_named_keys, LOCKED_FUNDS_PERIOD_KEY),
+            1_000
+        );
+        assert_eq!(
+            cl_value_under::<u64>(&entries, &auction_named_keys, UNBONDING_DELAY_KEY),
+            14
+        );
+        assert_eq!(
+            cl_value_under::<Ratio<U512>>(&entries, &mint_named_keys, ROUND_SEIGNIORAGE_RATE_KEY),
+            Ratio::new(U512::from(1), U512::from(100))
+        );
+    }
+
+    #[test]
+    fn should_fail_on_missing_named_key() {
+        let auction_named_keys = named_keys(&[AUCTION_DELAY_KEY]);
+        let mint_named_keys = NamedKeys::new();
+
+        let error = AuctionStateUpdate::new(&auction_named_keys, &mint_named_keys)
+            .with_validator_slots(7)
+            .unwrap_err();
+        assert_eq!(
+            error,
+            AuctionStateUpdateError::MissingNamedKey(VALIDATOR_SLOTS_KEY.to_string())
+        );
+
+        let error = AuctionStateUpdate::new(&auction_named_keys, &mint_named_keys)
+            .with_round_seigniorage_rate(Ratio::new(1, 100))
+            .unwrap_err();
+        assert_eq!(
+            error,
+            AuctionStateUpdateError::MissingNamedKey(ROUND_SEIGNIORAGE_RATE_KEY.to_string())
+        );
+    }
+}
@@ -8,6 +8,9 @@ pub const DEFAULT_MAX_QUERY_DEPTH: u64 = 5;
 pub const DEFAULT_MAX_ASSOCIATED_KEYS: u32 = 100;
 /// Default value for maximum runtime call stack height configuration option.
 pub const DEFAULT_MAX_RUNTIME_CALL_STACK_HEIGHT: u32 = 12;
+/// Default value for the maximum number of attempts made to read a system contract or its package
+/// during an upgrade.
+pub const DEFAULT_MAX_UPGRADE_READ_ATTEMPTS: u32 = 3;
 
 /// The runtime configuration of the execution engine
 #[derive(Debug, Copy, Clone)]
@@ -19,6 +22,7 @@ pub struct EngineConfig {
     /// [`Weight`](casper_types::account::Weight)s) for a single account.
     max_associated_keys: u32,
     max_runtime_call_stack_height: u32,
+    max_upgrade_read_attempts: u32,
     wasm_config: WasmConfig,
     system_config: SystemConfig,
 }
@@ -29,6 +33,7 @@ impl Default for EngineConfig {
             max_query_depth: DEFAULT_MAX_QUERY_DEPTH,
             max_associated_keys: DEFAULT_MAX_ASSOCIATED_KEYS,
             max_runtime_call_stack_height: DEFAULT_MAX_RUNTIME_CALL_STACK_HEIGHT,
+            max_upgrade_read_attempts: DEFAULT_MAX_UPGRADE_READ_ATTEMPTS,
             wasm_config: WasmConfig::default(),
             system_config: SystemConfig::default(),
         }
@@ -48,6 +53,7 @@ impl EngineConfig {
             max_query_depth,
             max_associated_keys,
             max_runtime_call_stack_height,
+            max_upgrade_read_attempts: DEFAULT_MAX_UPGRADE_READ_ATTEMPTS,
             wasm_config,
             system_config,
         }
@@ -63,6 +69,18 @@ impl EngineConfig {
         self.max_runtime_call_stack_height
     }
 
+    /// Returns the current max upgrade read attempts config.
+    pub fn max_upgrade_read_attempts(&self) -> u32 {
+        self.max_upgrade_read_attempts
+    }
+
+    /// Sets the maximum number of attempts made to read a system contract or its package during
+    /// an upgrade.  Values below `1` are treated as `1`.
+    pub fn with_max_upgrade_read_attempts(mut self, max_upgrade_read_attempts: u32) -> Self {
+        self.max_upgrade_read_attempts = max_upgrade_read_attempts.max(1);
+        self
+    }
+
     /// Returns the current wasm config.
     pub fn wasm_config(&self) -> &WasmConfig {
         &self.wasm_config
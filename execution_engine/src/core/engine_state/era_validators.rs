@@ -4,7 +4,7 @@ use thiserror::Error;
 use datasize::DataSize;
 
 use casper_hashing::Digest;
-use casper_types::ProtocolVersion;
+use casper_types::{EraId, ProtocolVersion};
 
 use crate::core::{engine_state::error::Error, runtime::stack::RuntimeStackOverflow};
 
@@ -61,3 +61,38 @@ impl GetEraValidatorsRequest {
         self.protocol_version
     }
 }
+
+/// Represents a request for the validator sets of a range of eras.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GetEraValidatorsRangeRequest {
+    state_hash: Digest,
+    start_era_id: EraId,
+    end_era_id: EraId,
+}
+
+impl GetEraValidatorsRangeRequest {
+    /// Creates a new [`GetEraValidatorsRangeRequest`] for the eras from `start_era_id` to
+    /// `end_era_id`, both inclusive.
+    pub fn new(state_hash: Digest, start_era_id: EraId, end_era_id: EraId) -> Self {
+        GetEraValidatorsRangeRequest {
+            state_hash,
+            start_era_id,
+            end_era_id,
+        }
+    }
+
+    /// Returns the state root hash.
+    pub fn state_hash(&self) -> Digest {
+        self.state_hash
+    }
+
+    /// Returns the first era of the range.
+    pub fn start_era_id(&self) -> EraId {
+        self.start_era_id
+    }
+
+    /// Returns the last era of the range.
+    pub fn end_era_id(&self) -> EraId {
+        self.end_era_id
+    }
+}
@@ -82,6 +82,9 @@ pub enum Error {
     /// An attempt to push to the runtime stack while already at the maximum height.
     #[error("Runtime stack overflow")]
     RuntimeStackOverflow,
+    /// A trie node reachable from a state root is missing from global state.
+    #[error("Missing trie node: {0}")]
+    MissingTrieNode(Digest),
 }
 
 impl Error {
@@ -1,5 +1,9 @@
 //! Effects that are produced as part of execution.
-use casper_types::Key;
+use casper_hashing::Digest;
+use casper_types::{
+    bytesrepr::{self, ToBytes},
+    Key,
+};
 
 use super::op::Op;
 use crate::shared::{
@@ -16,6 +20,81 @@ pub struct ExecutionEffect {
     pub transforms: AdditiveMap<Key, Transform>,
 }
 
+impl ExecutionEffect {
+    /// Appends the effects in `other` to these effects.
+    ///
+    /// Ops and transforms under the same key are combined with those in `other` applied after
+    /// these, so a write in `other` overrides anything already recorded under that key.
+    pub fn append(&mut self, other: ExecutionEffect) {
+        for (key, op) in other.ops {
+            self.ops.insert_add(key, op);
+        }
+        for (key, transform) in other.transforms {
+            self.transforms.insert_add(key, transform);
+        }
+    }
+
+    /// Returns a hash of the transforms, taken in key order so the hash doesn't depend on the
+    /// order in which they were recorded.
+    ///
+    /// Unlike the state root hash produced by committing the transforms, this distinguishes
+    /// effects which lead to the same state by different means, e.g. a read from no access at all.
+    pub fn hash(&self) -> Result<Digest, bytesrepr::Error> {
+        let mut transforms: Vec<(&Key, &Transform)> = self.transforms.iter().collect();
+        transforms.sort_by_key(|(key, _)| **key);
+
+        let mut bytes = Vec::new();
+        for (key, transform) in transforms {
+            bytes.append(&mut key.to_bytes()?);
+            append_transform_bytes(transform, &mut bytes)?;
+        }
+        Ok(Digest::hash(&bytes))
+    }
+}
+
+/// Appends a tagged serialization of `transform` to `bytes`.
+fn append_transform_bytes(
+    transform: &Transform,
+    bytes: &mut Vec<u8>,
+) -> Result<(), bytesrepr::Error> {
+    match transform {
+        Transform::Identity => bytes.push(0),
+        Transform::Write(stored_value) => {
+            bytes.push(1);
+            bytes.append(&mut stored_value.to_bytes()?);
+        }
+        Transform::AddInt32(value) => {
+            bytes.push(2);
+            bytes.append(&mut value.to_bytes()?);
+        }
+        Transform::AddUInt64(value) => {
+            bytes.push(3);
+            bytes.append(&mut value.to_bytes()?);
+        }
+        Transform::AddUInt128(value) => {
+            bytes.push(4);
+            bytes.append(&mut value.to_bytes()?);
+        }
+        Transform::AddUInt256(value) => {
+            bytes.push(5);
+            bytes.append(&mut value.to_bytes()?);
+        }
+        Transform::AddUInt512(value) => {
+            bytes.push(6);
+            bytes.append(&mut value.to_bytes()?);
+        }
+        Transform::AddKeys(named_keys) => {
+            bytes.push(7);
+            bytes.append(&mut named_keys.to_bytes()?);
+        }
+        Transform::Failure(error) => {
+            bytes.push(8);
+            bytes.append(&mut error.to_string().to_bytes()?);
+        }
+    }
+    Ok(())
+}
+
 impl From<ExecutionJournal> for ExecutionEffect {
     fn from(journal: ExecutionJournal) -> Self {
         let mut ops = AdditiveMap::new();
@@ -48,3 +127,76 @@ impl From<ExecutionJournal> for AdditiveMap<Key, Transform> {
         transforms
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{CLValue, StoredValue};
+
+    use super::*;
+
+    fn write(value: u64) -> Transform {
+        Transform::Write(StoredValue::CLValue(CLValue::from_t(value).unwrap()))
+    }
+
+    #[test]
+    fn should_append_overlapping_and_disjoint_effects() {
+        let overlapping_key = Key::Hash([1; 32]);
+        let first_key = Key::Hash([2; 32]);
+        let second_key = Key::Hash([3; 32]);
+
+        let mut effect = ExecutionEffect::default();
+        effect.ops.insert(overlapping_key, Op::Read);
+        effect.transforms.insert(overlapping_key, write(1));
+        effect.ops.insert(first_key, Op::Write);
+        effect.transforms.insert(first_key, write(2));
+
+        let mut other = ExecutionEffect::default();
+        other.ops.insert(overlapping_key, Op::Write);
+        other.transforms.insert(overlapping_key, write(3));
+        other.ops.insert(second_key, Op::Add);
+        other.transforms.insert(second_key, Transform::AddUInt64(4));
+
+        effect.append(other);
+
+        assert_eq!(effect.ops.len(), 3);
+        assert_eq!(effect.ops[&overlapping_key], Op::Read + Op::Write);
+        assert_eq!(effect.ops[&first_key], Op::Write);
+        assert_eq!(effect.ops[&second_key], Op::Add);
+
+        assert_eq!(effect.transforms.len(), 3);
+        assert_eq!(effect.transforms[&overlapping_key], write(3));
+        assert_eq!(effect.transforms[&first_key], write(2));
+        assert_eq!(effect.transforms[&second_key], Transform::AddUInt64(4));
+    }
+
+    #[test]
+    fn hash_should_not_depend_on_insertion_order() {
+        let first_key = Key::Hash([1; 32]);
+        let second_key = Key::Hash([2; 32]);
+
+        let mut effect = ExecutionEffect::default();
+        effect.transforms.insert(first_key, write(1));
+        effect
+            .transforms
+            .insert(second_key, Transform::AddUInt64(2));
+
+        let mut other = ExecutionEffect::default();
+        other.transforms.insert(second_key, Transform::AddUInt64(2));
+        other.transforms.insert(first_key, write(1));
+
+        assert_eq!(effect.hash().unwrap(), other.hash().unwrap());
+    }
+
+    #[test]
+    fn hash_should_distinguish_effects_leading_to_the_same_state() {
+        let key = Key::Hash([1; 32]);
+
+        let mut effect = ExecutionEffect::default();
+        effect.transforms.insert(key, Transform::Identity);
+
+        assert_ne!(
+            effect.hash().unwrap(),
+            ExecutionEffect::default().hash().unwrap()
+        );
+    }
+}
@@ -1,5 +1,5 @@
 //! Support for a genesis process.
-use std::{cell::RefCell, collections::BTreeMap, fmt, iter, rc::Rc};
+use std::{cell::RefCell, collections::BTreeMap, fmt, iter, rc::Rc, sync::Arc};
 
 use datasize::DataSize;
 use num::Zero;
@@ -46,7 +46,10 @@ use crate::{
         runtime::RuntimeStack,
         tracking_copy::{TrackingCopy, TrackingCopyExt},
     },
-    shared::{newtypes::CorrelationId, system_config::SystemConfig, wasm_config::WasmConfig},
+    shared::{
+        newtypes::CorrelationId, system_config::SystemConfig, wasm_config::WasmConfig,
+        wasm_module_cache::WasmModuleCache,
+    },
     storage::global_state::StateProvider,
 };
 
@@ -760,7 +763,8 @@ where
         tracking_copy: Rc<RefCell<TrackingCopy<<S as StateProvider>::Reader>>>,
         system_module: Module,
     ) -> Self {
-        let executor = Executor::new(engine_config);
+        // Genesis only installs and calls system contracts, so there are no stored modules to cache.
+        let executor = Executor::new(engine_config, Arc::new(WasmModuleCache::new(0)));
 
         let phase = Phase::System;
         let genesis_config_hash_bytes = genesis_config_hash.as_ref();
@@ -1,4 +1,5 @@
 //!  This module contains all the execution related code.
+pub mod auction_state_update;
 pub mod balance;
 pub mod deploy_item;
 pub mod engine_config;
@@ -13,48 +14,61 @@ pub mod get_bids;
 pub mod op;
 pub mod query;
 pub mod run_genesis_request;
+pub mod state_diff;
 pub mod step;
 mod transfer;
 pub mod upgrade;
+#[cfg(any(feature = "test-support", test))]
+pub mod upgrade_harness;
 
 use std::{
     cell::RefCell,
     collections::{BTreeMap, BTreeSet},
     convert::TryFrom,
+    fmt::{self, Debug, Formatter},
     iter::FromIterator,
     rc::Rc,
+    sync::{Arc, Mutex},
+    time::Instant,
 };
 
 use num::Zero;
 use num_rational::Ratio;
 use once_cell::sync::Lazy;
-use tracing::{debug, error};
+use tracing::{debug, error, info, warn};
 
 use casper_hashing::Digest;
 use casper_types::{
     account::{Account, AccountHash},
-    bytesrepr::ToBytes,
+    bytesrepr::{FromBytes, ToBytes},
     contracts::NamedKeys,
     system::{
         auction::{
-            EraValidators, ARG_ERA_END_TIMESTAMP_MILLIS, ARG_EVICTED_VALIDATORS,
-            ARG_REWARD_FACTORS, ARG_VALIDATOR_PUBLIC_KEYS, AUCTION_DELAY_KEY,
-            LOCKED_FUNDS_PERIOD_KEY, UNBONDING_DELAY_KEY, VALIDATOR_SLOTS_KEY,
+            EraValidators, SeigniorageRecipientsSnapshot, ValidatorWeights,
+            ARG_ERA_END_TIMESTAMP_MILLIS, ARG_EVICTED_VALIDATORS, ARG_REWARD_FACTORS,
+            ARG_VALIDATOR_PUBLIC_KEYS, AUCTION_DELAY_KEY, LOCKED_FUNDS_PERIOD_KEY,
+            SEIGNIORAGE_RECIPIENTS_SNAPSHOT_KEY, UNBONDING_DELAY_KEY, VALIDATOR_SLOTS_KEY,
         },
         handle_payment,
         mint::{self, ROUND_SEIGNIORAGE_RATE_KEY},
         CallStackElement, AUCTION, HANDLE_PAYMENT, MINT, STANDARD_PAYMENT,
     },
-    AccessRights, ApiError, BlockTime, CLValue, Contract, ContractHash, DeployHash, DeployInfo,
-    Gas, Key, KeyTag, Motes, Phase, ProtocolVersion, PublicKey, RuntimeArgs, StoredValue, URef,
-    U512,
+    AccessRights, ApiError, BlockTime, CLTyped, CLValue, Contract, ContractHash, DeployHash,
+    DeployInfo, Gas, Key, KeyTag, Motes, Phase, ProtocolVersion, PublicKey, RuntimeArgs,
+    StoredValue, StoredValueTypeMismatch, URef, U512,
 };
 
 pub use self::{
+    auction_state_update::{AuctionStateUpdate, AuctionStateUpdateError},
     balance::{BalanceRequest, BalanceResult},
     deploy_item::DeployItem,
-    engine_config::{EngineConfig, DEFAULT_MAX_QUERY_DEPTH, DEFAULT_MAX_RUNTIME_CALL_STACK_HEIGHT},
-    era_validators::{GetEraValidatorsError, GetEraValidatorsRequest},
+    engine_config::{
+        EngineConfig, DEFAULT_MAX_QUERY_DEPTH, DEFAULT_MAX_RUNTIME_CALL_STACK_HEIGHT,
+        DEFAULT_MAX_UPGRADE_READ_ATTEMPTS,
+    },
+    era_validators::{
+        GetEraValidatorsError, GetEraValidatorsRangeRequest, GetEraValidatorsRequest,
+    },
     error::Error,
     executable_deploy_item::{ExecutableDeployItem, ExecutableDeployItemIdentifier},
     execute_request::ExecuteRequest,
@@ -62,10 +76,15 @@ pub use self::{
     execution_result::{ExecutionResult, ExecutionResults, ForcedTransferResult},
     genesis::{ExecConfig, GenesisAccount, GenesisSuccess, SystemContractRegistry},
     get_bids::{GetBidsRequest, GetBidsResult},
-    query::{QueryRequest, QueryResult},
+    query::{BatchQueryRequest, QueryRequest, QueryResult},
+    state_diff::{StateDiff, ValueChange},
     step::{RewardItem, SlashItem, StepError, StepRequest, StepSuccess},
     transfer::{TransferArgs, TransferRuntimeArgsBuilder, TransferTargetMode},
-    upgrade::{UpgradeConfig, UpgradeSuccess},
+    upgrade::{
+        ConfigFieldChange, DefaultEntryPointsProvider, EntryPointsProvider, Migration,
+        UpgradeConfig, UpgradeCostEstimate, UpgradeObserver, UpgradeSuccess,
+        UpgradeValidationReport, ValidatedUpgradeConfig,
+    },
 };
 use crate::{
     core::{
@@ -73,6 +92,7 @@ use crate::{
             executable_deploy_item::DeployKind,
             execution_result::ExecutionResultBuilder,
             genesis::GenesisInstaller,
+            state_diff::StateDiffer,
             upgrade::{ProtocolUpgradeError, SystemUpgrader},
         },
         execution::{self, DirectSystemContractCall, Executor},
@@ -81,10 +101,10 @@ use crate::{
     },
     shared::{
         additive_map::AdditiveMap, newtypes::CorrelationId, transform::Transform,
-        wasm_prep::Preprocessor,
+        wasm_module_cache::WasmModuleCache, wasm_prep::Preprocessor,
     },
     storage::{
-        global_state::{lmdb::LmdbGlobalState, StateProvider},
+        global_state::{lmdb::LmdbGlobalState, StateProvider, StateReader},
         trie::Trie,
     },
 };
@@ -107,10 +127,26 @@ pub const WASMLESS_TRANSFER_FIXED_GAS_PRICE: u64 = 1;
 /// Takes an engine's configuration and a provider of a state (aka the global state) to operate on.
 /// Methods implemented on this structure are the external API intended to be used by the users such
 /// as the node, test framework, and others.
-#[derive(Debug)]
 pub struct EngineState<S> {
     config: EngineConfig,
     state: S,
+    upgrade_observers: Mutex<Vec<UpgradeObserver>>,
+    wasm_module_cache: Arc<WasmModuleCache>,
+    network_name: Option<String>,
+    supported_upgrade_paths: Vec<(ProtocolVersion, ProtocolVersion)>,
+}
+
+impl<S: Debug> Debug for EngineState<S> {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("EngineState")
+            .field("config", &self.config)
+            .field("state", &self.state)
+            .field("wasm_module_cache", &self.wasm_module_cache)
+            .field("network_name", &self.network_name)
+            .field("supported_upgrade_paths", &self.supported_upgrade_paths)
+            .finish()
+    }
 }
 
 impl EngineState<LmdbGlobalState> {
@@ -130,7 +166,53 @@ where
 {
     /// Creates new engine state.
     pub fn new(state: S, config: EngineConfig) -> EngineState<S> {
-        EngineState { config, state }
+        EngineState {
+            config,
+            state,
+            upgrade_observers: Mutex::new(Vec::new()),
+            wasm_module_cache: Arc::new(WasmModuleCache::default()),
+            network_name: None,
+            supported_upgrade_paths: upgrade::SUPPORTED_UPGRADE_PATHS.to_vec(),
+        }
+    }
+
+    /// Replaces the cache of stored contracts' Wasm modules with one holding at most
+    /// `max_entries` modules.  A size of `0` disables the cache.
+    pub fn with_wasm_module_cache_size(mut self, max_entries: usize) -> Self {
+        self.wasm_module_cache = Arc::new(WasmModuleCache::new(max_entries));
+        self
+    }
+
+    /// Binds the engine state to the network named `network_name`, so that upgrades intended for
+    /// another network are rejected.
+    pub fn with_network_name(mut self, network_name: &str) -> Self {
+        self.network_name = Some(network_name.to_string());
+        self
+    }
+
+    /// Returns the name of the network the engine state is bound to, if any.
+    pub fn network_name(&self) -> Option<&str> {
+        self.network_name.as_deref()
+    }
+
+    /// Replaces the upgrade paths which upgrades must follow, by default
+    /// [`upgrade::SUPPORTED_UPGRADE_PATHS`].
+    pub fn with_supported_upgrade_paths(
+        mut self,
+        supported_upgrade_paths: Vec<(ProtocolVersion, ProtocolVersion)>,
+    ) -> Self {
+        self.supported_upgrade_paths = supported_upgrade_paths;
+        self
+    }
+
+    /// Returns the upgrade paths which upgrades must follow.
+    pub fn supported_upgrade_paths(&self) -> &[(ProtocolVersion, ProtocolVersion)] {
+        &self.supported_upgrade_paths
+    }
+
+    /// Returns the cache of stored contracts' Wasm modules.
+    pub fn wasm_module_cache(&self) -> &WasmModuleCache {
+        &self.wasm_module_cache
     }
 
     /// Returns engine config.
@@ -222,6 +304,14 @@ where
         })
     }
 
+    /// Registers `observer` to be called with the result of every upgrade committed from now on.
+    pub fn register_upgrade_observer(&self, observer: UpgradeObserver) {
+        self.upgrade_observers
+            .lock()
+            .expect("upgrade observers lock poisoned")
+            .push(observer);
+    }
+
     /// Commits upgrade.
     ///
     /// This process applies changes to the global state.
@@ -230,19 +320,125 @@ where
     pub fn commit_upgrade(
         &self,
         correlation_id: CorrelationId,
-        upgrade_config: UpgradeConfig,
+        upgrade_config: ValidatedUpgradeConfig,
+    ) -> Result<UpgradeSuccess, Error> {
+        self.commit_upgrade_with_migrations(correlation_id, upgrade_config, &[])
+    }
+
+    /// Commits upgrade, running the given migrations in order after the system contracts and the
+    /// global state update have been applied.
+    ///
+    /// The upgrade is applied in fixed phases, each seeing the writes of the ones before it:
+    /// 1. system contracts are upgraded (on a major version bump),
+    /// 2. the optional auction and mint config values and config flags are written,
+    /// 3. the `global_state_update` entries are written, in key order,
+    /// 4. the migrations are run, in the order given.
+    ///
+    /// Where several phases touch the same key, the later phase wins. Every node must apply the
+    /// phases in this order to arrive at the same post state hash.
+    ///
+    /// A no-op upgrade (see [`UpgradeConfig::is_no_op`]) without migrations skips all phases and
+    /// leaves global state untouched.
+    ///
+    /// Returns [`UpgradeSuccess`].
+    pub fn commit_upgrade_with_migrations(
+        &self,
+        correlation_id: CorrelationId,
+        upgrade_config: ValidatedUpgradeConfig,
+        migrations: &[Box<dyn Migration>],
     ) -> Result<UpgradeSuccess, Error> {
         // per specification:
         // https://casperlabs.atlassian.net/wiki/spaces/EN/pages/139854367/Upgrading+System+Contracts+Specification
 
+        let start = Instant::now();
+
         // 3.1.1.1.1.1 validate pre state hash exists
         // 3.1.2.1 get a tracking_copy at the provided pre_state_hash
-        let pre_state_hash = upgrade_config.pre_state_hash();
+        let pre_state_hash = Digest::from(upgrade_config.pre_state_hash());
         let tracking_copy = match self.tracking_copy(pre_state_hash)? {
             Some(tracking_copy) => Rc::new(RefCell::new(tracking_copy)),
             None => return Err(Error::RootNotFound(pre_state_hash)),
         };
 
+        self.apply_upgrade(correlation_id, &tracking_copy, &upgrade_config, migrations)?;
+
+        self.commit_upgrade_effects(
+            correlation_id,
+            pre_state_hash,
+            &tracking_copy,
+            upgrade_config.current_protocol_version(),
+            upgrade_config.new_protocol_version(),
+            start,
+        )
+    }
+
+    /// Applies a chain of upgrades, each on top of the state left by the one before it, and
+    /// commits them together.
+    ///
+    /// The upgrades are applied against the pre-state hash of the first config; the pre-state
+    /// hashes of the later configs are ignored, as the intermediate states are never committed.
+    /// Each config must upgrade from the protocol version the previous one upgraded to.
+    ///
+    /// The configs are all validated before any is applied. If any upgrade fails, nothing is
+    /// committed. Otherwise the returned [`UpgradeSuccess`] holds the combined effects of all
+    /// upgrades and the final post state hash.
+    pub fn apply_upgrades(
+        &self,
+        correlation_id: CorrelationId,
+        upgrade_configs: Vec<UpgradeConfig>,
+    ) -> Result<UpgradeSuccess, Error> {
+        let start = Instant::now();
+
+        let first_upgrade_config = upgrade_configs.first().ok_or_else(|| {
+            error!("No upgrade configs to apply");
+            Error::ProtocolUpgrade(ProtocolUpgradeError::InvalidUpgradeConfig)
+        })?;
+
+        let is_chained = upgrade_configs
+            .windows(2)
+            .all(|pair| pair[0].new_protocol_version() == pair[1].current_protocol_version());
+        if !is_chained {
+            error!("Upgrade configs don't form a chain of protocol versions");
+            return Err(Error::ProtocolUpgrade(
+                ProtocolUpgradeError::InvalidUpgradeConfig,
+            ));
+        }
+
+        let validated_upgrade_configs = upgrade_configs
+            .iter()
+            .map(UpgradeConfig::validate)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Error::ProtocolUpgrade)?;
+
+        let pre_state_hash = Digest::from(first_upgrade_config.pre_state_hash());
+        let tracking_copy = match self.tracking_copy(pre_state_hash)? {
+            Some(tracking_copy) => Rc::new(RefCell::new(tracking_copy)),
+            None => return Err(Error::RootNotFound(pre_state_hash)),
+        };
+
+        for upgrade_config in &validated_upgrade_configs {
+            self.apply_upgrade(correlation_id, &tracking_copy, upgrade_config, &[])?;
+        }
+
+        let last_upgrade_config = upgrade_configs.last().unwrap_or(first_upgrade_config);
+        self.commit_upgrade_effects(
+            correlation_id,
+            pre_state_hash,
+            &tracking_copy,
+            first_upgrade_config.current_protocol_version(),
+            last_upgrade_config.new_protocol_version(),
+            start,
+        )
+    }
+
+    /// Applies the phases of an upgrade to `tracking_copy`, without committing anything.
+    fn apply_upgrade(
+        &self,
+        correlation_id: CorrelationId,
+        tracking_copy: &Rc<RefCell<TrackingCopy<S::Reader>>>,
+        upgrade_config: &ValidatedUpgradeConfig,
+        migrations: &[Box<dyn Migration>],
+    ) -> Result<(), Error> {
         // 3.1.1.1.1.2 current protocol version is required
         let current_protocol_version = upgrade_config.current_protocol_version();
 
@@ -257,6 +453,32 @@ where
             return Err(Error::InvalidProtocolVersion(new_protocol_version));
         }
 
+        if let Some(network_name) = self.network_name() {
+            upgrade_config
+                .check_network_name(network_name)
+                .map_err(Error::ProtocolUpgrade)?;
+        }
+
+        if !upgrade_config.is_supported_path_in(&self.supported_upgrade_paths) {
+            error!(
+                from = %current_protocol_version,
+                to = %new_protocol_version,
+                "unsupported upgrade path"
+            );
+            return Err(Error::ProtocolUpgrade(
+                ProtocolUpgradeError::UnsupportedUpgradePath {
+                    from: current_protocol_version,
+                    to: new_protocol_version,
+                },
+            ));
+        }
+
+        // skip the upgrade entirely if it wouldn't change anything
+        if upgrade_config.is_no_op() && migrations.is_empty() {
+            return Ok(());
+        }
+
+        let mut registry_written = false;
         let registry = if let Ok(registry) = tracking_copy
             .borrow_mut()
             .get_system_contracts(correlation_id)
@@ -268,16 +490,27 @@ where
                 .global_state_update()
                 .get(&Key::SystemContractRegistry)
                 .ok_or_else(|| {
-                    error!("Registry is absent in upgrade config");
-                    Error::ProtocolUpgrade(ProtocolUpgradeError::FailedToCreateSystemRegistry)
+                    error!("Registry is absent in global state and upgrade config");
+                    Error::ProtocolUpgrade(ProtocolUpgradeError::MissingSystemContractRegistry)
                 })?
                 .to_owned();
             if let StoredValue::CLValue(cl_registry) = upgrade_registry {
-                CLValue::into_t::<SystemContractRegistry>(cl_registry).map_err(|error| {
-                    let error_msg = format!("Conversion to system registry failed: {:?}", error);
-                    error!("{}", error_msg);
-                    Error::Bytesrepr(error_msg)
-                })?
+                let registry = CLValue::into_t::<SystemContractRegistry>(cl_registry.clone())
+                    .map_err(|error| {
+                        let error_msg =
+                            format!("Conversion to system registry failed: {:?}", error);
+                        error!("{}", error_msg);
+                        Error::Bytesrepr(error_msg)
+                    })?;
+                // Write the registry ahead of the rest of the global state update so it's
+                // available to the system contract upgrade. It's skipped when the rest of the
+                // update is applied below.
+                tracking_copy.borrow_mut().write(
+                    Key::SystemContractRegistry,
+                    StoredValue::CLValue(cl_registry),
+                );
+                registry_written = true;
+                registry
             } else {
                 error!("Failed to create registry as StoreValue in upgrade config is not CLValue");
                 return Err(Error::ProtocolUpgrade(
@@ -305,8 +538,13 @@ where
 
         // 3.1.1.1.1.5 bump system contract major versions
         if upgrade_check_result.is_major_version() {
-            let system_upgrader: SystemUpgrader<S> =
-                SystemUpgrader::new(new_protocol_version, tracking_copy.clone());
+            let system_upgrader: SystemUpgrader<S::Reader> = SystemUpgrader::new(
+                new_protocol_version,
+                upgrade_config.disabled_versions_retention(),
+                self.config.max_upgrade_read_attempts(),
+                tracking_copy.clone(),
+                None,
+            );
 
             system_upgrader
                 .upgrade_system_contracts_major_version(
@@ -395,28 +633,306 @@ where
                 .write(locked_funds_period_key, value);
         }
 
-        // apply the arbitrary modifications
+        if let Some(new_config_flags) = upgrade_config.new_config_flags() {
+            for (flag_name, value) in new_config_flags {
+                tracking_copy.borrow_mut().write(
+                    upgrade::config_flag_key(flag_name),
+                    StoredValue::CLValue(value.clone()),
+                );
+            }
+        }
+
+        // guard the system contracts and their packages against being clobbered by the update;
+        // only hash keys can collide with them
+        let strict = upgrade_config.strict_global_state_update();
+        if upgrade_config
+            .global_state_update()
+            .keys()
+            .any(|key| matches!(key, Key::Hash(_)))
+        {
+            let mut system_contract_keys = BTreeSet::new();
+            for (name, contract_hash) in registry.iter() {
+                system_contract_keys.insert(Key::from(*contract_hash));
+                match tracking_copy
+                    .borrow_mut()
+                    .get_contract(correlation_id, *contract_hash)
+                {
+                    Ok(contract) => {
+                        system_contract_keys.insert(Key::from(contract.contract_package_hash()));
+                    }
+                    Err(error) if strict => return Err(error.into()),
+                    Err(error) => {
+                        warn!(%name, %error, "unable to read system contract to guard its package")
+                    }
+                }
+            }
+            for key in upgrade_config
+                .global_state_update()
+                .keys()
+                .filter(|key| system_contract_keys.contains(key))
+            {
+                if strict {
+                    error!(%key, "global state update targets a system contract");
+                    return Err(Error::ProtocolUpgrade(
+                        ProtocolUpgradeError::UpdateTargetsSystemContract(*key),
+                    ));
+                }
+                warn!(%key, "global state update targets a system contract");
+            }
+        }
+
+        // apply the arbitrary modifications, overriding any config values written above
         for (key, value) in upgrade_config.global_state_update() {
+            if let Some(note) = upgrade_config.global_state_update_notes().get(key) {
+                info!(%key, %note, "applying annotated global state update entry");
+            }
+            if registry_written && *key == Key::SystemContractRegistry {
+                continue;
+            }
             tracking_copy.borrow_mut().write(*key, value.clone());
         }
 
+        // run the programmable migrations last, so they see the global state update
+        for migration in migrations {
+            for key in migration.keys() {
+                let value = tracking_copy
+                    .borrow_mut()
+                    .read(correlation_id, &key)
+                    .map_err(Into::<execution::Error>::into)?
+                    .ok_or_else(|| {
+                        error!("Missing value to migrate under {}", key);
+                        Error::ProtocolUpgrade(ProtocolUpgradeError::FailedMigration(format!(
+                            "missing value under {}",
+                            key
+                        )))
+                    })?;
+                let migrated_value = migration
+                    .migrate(&key, value)
+                    .map_err(Error::ProtocolUpgrade)?;
+                tracking_copy.borrow_mut().write(key, migrated_value);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Commits the effects recorded in `tracking_copy` on top of `pre_state_hash`.
+    ///
+    /// If there are no effects, nothing is committed and the post state hash is `pre_state_hash`.
+    /// On success, a single summary of the upgrade from `from_version` to `to_version`, started at
+    /// `start`, is logged.
+    fn commit_upgrade_effects(
+        &self,
+        correlation_id: CorrelationId,
+        pre_state_hash: Digest,
+        tracking_copy: &Rc<RefCell<TrackingCopy<S::Reader>>>,
+        from_version: ProtocolVersion,
+        to_version: ProtocolVersion,
+        start: Instant,
+    ) -> Result<UpgradeSuccess, Error> {
         let execution_effect = tracking_copy.borrow().effect();
+        let execution_journal = tracking_copy.borrow().execution_journal();
 
         // commit
-        let post_state_hash = self
-            .state
-            .commit(
+        let post_state_hash = if execution_effect.transforms.is_empty() {
+            pre_state_hash
+        } else {
+            self.state
+                .commit(
+                    correlation_id,
+                    pre_state_hash,
+                    execution_effect.transforms.to_owned(),
+                )
+                .map_err(Into::into)?
+        };
+
+        let effect_hash = execution_effect.hash()?;
+
+        let contracts_upgraded = execution_effect
+            .transforms
+            .values()
+            .filter(|transform| matches!(transform, Transform::Write(StoredValue::Contract(_))))
+            .count();
+        info!(
+            %from_version,
+            %to_version,
+            contracts_upgraded,
+            entries_written = execution_effect.transforms.len(),
+            %post_state_hash,
+            duration = ?start.elapsed(),
+            "upgrade complete"
+        );
+
+        let upgrade_success = UpgradeSuccess {
+            post_state_hash: post_state_hash.into(),
+            execution_effect,
+            effect_hash,
+            execution_journal,
+        };
+
+        // Observers are called without holding the lock, so they may register further observers.
+        let upgrade_observers = self
+            .upgrade_observers
+            .lock()
+            .expect("upgrade observers lock poisoned")
+            .clone();
+        for observer in upgrade_observers {
+            observer(&upgrade_success);
+        }
+
+        // return result and effects
+        Ok(upgrade_success)
+    }
+
+    /// Estimates the global state accesses of upgrading the system contracts as part of
+    /// `upgrade_config`, without writing anything.
+    ///
+    /// System contracts are only upgraded on a major version bump, so other upgrades are estimated
+    /// to touch none. Add the length of the global state update to
+    /// [`UpgradeCostEstimate::writes`] for the total number of entries written.
+    pub fn estimate_upgrade_cost(
+        &self,
+        correlation_id: CorrelationId,
+        upgrade_config: &UpgradeConfig,
+    ) -> Result<UpgradeCostEstimate, Error> {
+        let is_major_version = upgrade_config
+            .current_protocol_version()
+            .check_next_version(&upgrade_config.new_protocol_version())
+            .is_major_version();
+        if !is_major_version {
+            return Ok(UpgradeCostEstimate::default());
+        }
+
+        let pre_state_hash = Digest::from(upgrade_config.pre_state_hash());
+        let tracking_copy = match self.tracking_copy(pre_state_hash)? {
+            Some(tracking_copy) => Rc::new(RefCell::new(tracking_copy)),
+            None => return Err(Error::RootNotFound(pre_state_hash)),
+        };
+
+        let registry = tracking_copy
+            .borrow_mut()
+            .get_system_contracts(correlation_id)?;
+        let system_contract_hash = |name: &str| {
+            registry.get(name).copied().ok_or_else(|| {
+                error!("Missing system {} contract hash", name);
+                Error::MissingSystemContractHash(name.to_string())
+            })
+        };
+        let mint_hash = system_contract_hash(MINT)?;
+        let auction_hash = system_contract_hash(AUCTION)?;
+        let handle_payment_hash = system_contract_hash(HANDLE_PAYMENT)?;
+        let standard_payment_hash = system_contract_hash(STANDARD_PAYMENT)?;
+
+        let system_upgrader: SystemUpgrader<S::Reader> = SystemUpgrader::new(
+            upgrade_config.new_protocol_version(),
+            upgrade_config.disabled_versions_retention(),
+            self.config.max_upgrade_read_attempts(),
+            tracking_copy,
+            None,
+        );
+        system_upgrader
+            .estimate_cost(
                 correlation_id,
-                pre_state_hash,
-                execution_effect.transforms.to_owned(),
+                &mint_hash,
+                &auction_hash,
+                &handle_payment_hash,
+                &standard_payment_hash,
             )
-            .map_err(Into::into)?;
+            .map_err(Error::ProtocolUpgrade)
+    }
 
-        // return result and effects
-        Ok(UpgradeSuccess {
-            post_state_hash,
-            execution_effect,
-        })
+    /// Returns the tuning parameters which `upgrade_config` would change relative to the values
+    /// currently active in global state under its pre-state hash.
+    ///
+    /// Parameters left unset in the upgrade config are considered unchanged.
+    pub fn get_upgrade_config_changes(
+        &self,
+        correlation_id: CorrelationId,
+        upgrade_config: &UpgradeConfig,
+    ) -> Result<Vec<ConfigFieldChange>, Error> {
+        let pre_state_hash = Digest::from(upgrade_config.pre_state_hash());
+        let mut tracking_copy = match self.tracking_copy(pre_state_hash)? {
+            Some(tracking_copy) => tracking_copy,
+            None => return Err(Error::RootNotFound(pre_state_hash)),
+        };
+
+        let registry = tracking_copy.get_system_contracts(correlation_id)?;
+        let mint_hash = *registry.get(MINT).ok_or_else(|| {
+            error!("Missing system mint contract hash");
+            Error::MissingSystemContractHash(MINT.to_string())
+        })?;
+        let auction_hash = *registry.get(AUCTION).ok_or_else(|| {
+            error!("Missing system auction contract hash");
+            Error::MissingSystemContractHash(AUCTION.to_string())
+        })?;
+
+        let mut changes = Vec::new();
+
+        if let Some(new) = upgrade_config.new_validator_slots() {
+            let current: u32 = read_named_value(
+                &mut tracking_copy,
+                correlation_id,
+                auction_hash,
+                VALIDATOR_SLOTS_KEY,
+            )?;
+            if current != new {
+                changes.push(ConfigFieldChange::ValidatorSlots { current, new });
+            }
+        }
+
+        if let Some(new) = upgrade_config.new_auction_delay() {
+            let current: u64 = read_named_value(
+                &mut tracking_copy,
+                correlation_id,
+                auction_hash,
+                AUCTION_DELAY_KEY,
+            )?;
+            if current != new {
+                changes.push(ConfigFieldChange::AuctionDelay { current, new });
+            }
+        }
+
+        if let Some(new) = upgrade_config.new_locked_funds_period_millis() {
+            let current: u64 = read_named_value(
+                &mut tracking_copy,
+                correlation_id,
+                auction_hash,
+                LOCKED_FUNDS_PERIOD_KEY,
+            )?;
+            if current != new {
+                changes.push(ConfigFieldChange::LockedFundsPeriodMillis { current, new });
+            }
+        }
+
+        if let Some(new) = upgrade_config.new_unbonding_delay() {
+            let current: u64 = read_named_value(
+                &mut tracking_copy,
+                correlation_id,
+                auction_hash,
+                UNBONDING_DELAY_KEY,
+            )?;
+            if current != new {
+                changes.push(ConfigFieldChange::UnbondingDelay { current, new });
+            }
+        }
+
+        if let Some(new_round_seigniorage_rate) = upgrade_config.new_round_seigniorage_rate() {
+            let new: Ratio<U512> = {
+                let (numer, denom) = new_round_seigniorage_rate.into();
+                Ratio::new(numer.into(), denom.into())
+            };
+            let current: Ratio<U512> = read_named_value(
+                &mut tracking_copy,
+                correlation_id,
+                mint_hash,
+                ROUND_SEIGNIORAGE_RATE_KEY,
+            )?;
+            if current != new {
+                changes.push(ConfigFieldChange::RoundSeigniorageRate { current, new });
+            }
+        }
+
+        Ok(changes)
     }
 
     /// Creates a new tracking copy instance.
@@ -455,6 +971,33 @@ where
             .into())
     }
 
+    /// Runs several global state queries against one state root hash.
+    ///
+    /// All queries share a single tracking copy.  Returns one [`QueryResult`] per query, in the
+    /// order of the request; if the state root hash is unknown, each of them is
+    /// [`QueryResult::RootNotFound`].
+    pub fn run_batch_query(
+        &self,
+        correlation_id: CorrelationId,
+        batch_query_request: BatchQueryRequest,
+    ) -> Result<Vec<QueryResult>, Error> {
+        let queries = batch_query_request.queries();
+        let tracking_copy = match self.tracking_copy(batch_query_request.state_hash())? {
+            Some(tracking_copy) => tracking_copy,
+            None => return Ok(queries.iter().map(|_| QueryResult::RootNotFound).collect()),
+        };
+
+        queries
+            .iter()
+            .map(|(key, path)| {
+                Ok(tracking_copy
+                    .query(correlation_id, self.config(), *key, path)
+                    .map_err(|err| Error::Exec(err.into()))?
+                    .into())
+            })
+            .collect()
+    }
+
     /// Runs a deploy execution request.
     ///
     /// For each deploy stored in the request it will execute it.
@@ -467,43 +1010,87 @@ where
         correlation_id: CorrelationId,
         mut exec_request: ExecuteRequest,
     ) -> Result<ExecutionResults, Error> {
-        let executor = Executor::new(*self.config());
+        let executor = Executor::new(*self.config(), Arc::clone(&self.wasm_module_cache));
 
         let deploys = exec_request.take_deploys();
         let mut results = ExecutionResults::with_capacity(deploys.len());
 
         for deploy_item in deploys {
-            let result = match deploy_item.session {
-                ExecutableDeployItem::Transfer { .. } => self.transfer(
-                    correlation_id,
-                    &executor,
-                    exec_request.protocol_version,
-                    exec_request.parent_state_hash,
-                    BlockTime::new(exec_request.block_time),
-                    deploy_item,
-                    exec_request.proposer.clone(),
-                ),
-                _ => self.deploy(
-                    correlation_id,
-                    &executor,
-                    exec_request.protocol_version,
-                    exec_request.parent_state_hash,
-                    BlockTime::new(exec_request.block_time),
-                    deploy_item,
-                    exec_request.proposer.clone(),
-                ),
-            };
-            match result {
-                Ok(result) => results.push_back(result),
-                Err(error) => {
-                    return Err(error);
-                }
-            };
+            let result = self.execute_deploy_item(
+                correlation_id,
+                &executor,
+                exec_request.protocol_version,
+                exec_request.parent_state_hash,
+                BlockTime::new(exec_request.block_time),
+                deploy_item,
+                exec_request.proposer.clone(),
+            )?;
+            results.push_back(result);
         }
 
         Ok(results)
     }
 
+    /// Runs a single deploy against the given state root hash without committing its effects.
+    ///
+    /// The returned [`ExecutionResult`] carries the gas cost and the execution journal of the
+    /// deploy, which callers can use to estimate the cost of a deploy or to find out why it fails
+    /// before submitting it.
+    pub fn run_speculative_exec(
+        &self,
+        correlation_id: CorrelationId,
+        state_root_hash: Digest,
+        block_time: u64,
+        protocol_version: ProtocolVersion,
+        deploy_item: DeployItem,
+        proposer: PublicKey,
+    ) -> Result<ExecutionResult, Error> {
+        let executor = Executor::new(*self.config(), Arc::clone(&self.wasm_module_cache));
+        self.execute_deploy_item(
+            correlation_id,
+            &executor,
+            protocol_version,
+            state_root_hash,
+            BlockTime::new(block_time),
+            deploy_item,
+            proposer,
+        )
+    }
+
+    /// Executes a single deploy, taking the native transfer shortcut where applicable.
+    #[allow(clippy::too_many_arguments)]
+    fn execute_deploy_item(
+        &self,
+        correlation_id: CorrelationId,
+        executor: &Executor,
+        protocol_version: ProtocolVersion,
+        prestate_hash: Digest,
+        blocktime: BlockTime,
+        deploy_item: DeployItem,
+        proposer: PublicKey,
+    ) -> Result<ExecutionResult, Error> {
+        match deploy_item.session {
+            ExecutableDeployItem::Transfer { .. } => self.transfer(
+                correlation_id,
+                executor,
+                protocol_version,
+                prestate_hash,
+                blocktime,
+                deploy_item,
+                proposer,
+            ),
+            _ => self.deploy(
+                correlation_id,
+                executor,
+                protocol_version,
+                prestate_hash,
+                blocktime,
+                deploy_item,
+                proposer,
+            ),
+        }
+    }
+
     fn get_authorized_account(
         &self,
         correlation_id: CorrelationId,
@@ -1233,6 +1820,7 @@ where
             &account,
             correlation_id,
             &preprocessor,
+            &self.wasm_module_cache,
             &protocol_version,
             system_contract_registry,
             Phase::Session,
@@ -1316,6 +1904,7 @@ where
                 &account,
                 correlation_id,
                 &preprocessor,
+                &self.wasm_module_cache,
                 &protocol_version,
                 system_contract_registry,
                 phase,
@@ -1768,6 +2357,33 @@ where
             .map_err(Error::from)
     }
 
+    /// Returns the values which differ between the global states under `pre_state_hash` and
+    /// `post_state_hash`.
+    ///
+    /// Subtries which are shared by both state roots are skipped, so the cost of the walk is
+    /// proportional to the size of the change rather than to the size of global state.
+    pub fn get_state_diff(
+        &self,
+        correlation_id: CorrelationId,
+        pre_state_hash: Digest,
+        post_state_hash: Digest,
+    ) -> Result<StateDiff, Error>
+    where
+        Error: From<S::Error>,
+    {
+        for state_hash in &[pre_state_hash, post_state_hash] {
+            if self.state.get_trie(correlation_id, state_hash)?.is_none() {
+                return Err(Error::RootNotFound(*state_hash));
+            }
+        }
+        StateDiffer::new(|trie_key: &Digest| {
+            self.state
+                .get_trie(correlation_id, trie_key)?
+                .ok_or(Error::MissingTrieNode(*trie_key))
+        })
+        .diff(pre_state_hash, post_state_hash)
+    }
+
     /// Puts a trie and finds missing descendant trie keys.
     pub fn put_trie_and_find_missing_descendant_trie_keys(
         &self,
@@ -1838,7 +2454,7 @@ where
                 .map_err(Error::from)?
         };
 
-        let executor = Executor::new(*self.config());
+        let executor = Executor::new(*self.config(), Arc::clone(&self.wasm_module_cache));
 
         let mut named_keys = auction_contract.named_keys().to_owned();
         let base_key = Key::from(*auction_contract_hash);
@@ -1902,6 +2518,69 @@ where
         }
     }
 
+    /// Obtains validator weights for a range of eras.
+    ///
+    /// Reads the auction's seigniorage recipients snapshot directly from global state rather than
+    /// calling the auction contract.  The snapshot at a given state root only holds the current era
+    /// and the eras up to the auction delay ahead of it; eras of the range outside of it are
+    /// absent from the result.
+    pub fn get_era_validators_range(
+        &self,
+        correlation_id: CorrelationId,
+        get_era_validators_range_request: GetEraValidatorsRangeRequest,
+    ) -> Result<EraValidators, GetEraValidatorsError> {
+        let mut tracking_copy =
+            match self.tracking_copy(get_era_validators_range_request.state_hash())? {
+                Some(tracking_copy) => tracking_copy,
+                None => return Err(GetEraValidatorsError::RootNotFound),
+            };
+
+        let system_contract_registry = tracking_copy
+            .get_system_contracts(correlation_id)
+            .map_err(Error::from)?;
+        let auction_contract_hash = system_contract_registry.get(AUCTION).ok_or_else(|| {
+            error!("Missing system auction contract hash");
+            Error::MissingSystemContractHash(AUCTION.to_string())
+        })?;
+        let auction_contract = tracking_copy
+            .get_contract(correlation_id, *auction_contract_hash)
+            .map_err(Error::from)?;
+
+        let snapshot_key = match auction_contract
+            .named_keys()
+            .get(SEIGNIORAGE_RECIPIENTS_SNAPSHOT_KEY)
+        {
+            Some(snapshot_key) => *snapshot_key,
+            None => return Err(GetEraValidatorsError::EraValidatorsMissing),
+        };
+        let snapshot: SeigniorageRecipientsSnapshot = match tracking_copy
+            .read(correlation_id, &snapshot_key)
+            .map_err(|error| Error::Exec(error.into()))?
+        {
+            Some(StoredValue::CLValue(cl_value)) => cl_value
+                .into_t()
+                .map_err(|_| Error::Bytesrepr(SEIGNIORAGE_RECIPIENTS_SNAPSHOT_KEY.to_string()))?,
+            _ => return Err(GetEraValidatorsError::EraValidatorsMissing),
+        };
+
+        let era_range = get_era_validators_range_request.start_era_id()
+            ..=get_era_validators_range_request.end_era_id();
+        let era_validators = snapshot
+            .into_iter()
+            .filter(|(era_id, _)| era_range.contains(era_id))
+            .map(|(era_id, recipients)| {
+                let validator_weights = recipients
+                    .into_iter()
+                    .filter_map(|(public_key, recipient)| {
+                        recipient.total_stake().map(|stake| (public_key, stake))
+                    })
+                    .collect::<ValidatorWeights>();
+                (era_id, validator_weights)
+            })
+            .collect();
+        Ok(era_validators)
+    }
+
     /// Gets current bids from the auction system.
     pub fn get_bids(
         &self,
@@ -1944,7 +2623,7 @@ where
             Ok(Some(tracking_copy)) => Rc::new(RefCell::new(tracking_copy)),
         };
 
-        let executor = Executor::new(*self.config());
+        let executor = Executor::new(*self.config(), Arc::clone(&self.wasm_module_cache));
 
         let preprocessor = {
             let config = self.config();
@@ -2291,4 +2970,54 @@ where
         })?;
         Ok(*standard_payment)
     }
+
+    /// Returns the name of the system contract with the given hash, or `None` if the hash doesn't
+    /// belong to a system contract.
+    pub fn get_system_contract_name(
+        &self,
+        correlation_id: CorrelationId,
+        state_hash: Digest,
+        contract_hash: ContractHash,
+    ) -> Result<Option<String>, Error> {
+        let mut tracking_copy = match self.tracking_copy(state_hash)? {
+            None => return Err(Error::RootNotFound(state_hash)),
+            Some(tracking_copy) => tracking_copy,
+        };
+        upgrade::system_contract_name_for(&mut tracking_copy, correlation_id, contract_hash)
+            .map_err(Error::ProtocolUpgrade)
+    }
+}
+
+/// Reads the value stored under the named key `name` of the given contract.
+fn read_named_value<R, T>(
+    tracking_copy: &mut TrackingCopy<R>,
+    correlation_id: CorrelationId,
+    contract_hash: ContractHash,
+    name: &str,
+) -> Result<T, Error>
+where
+    R: StateReader<Key, StoredValue>,
+    R::Error: Into<execution::Error>,
+    T: CLTyped + FromBytes,
+{
+    let contract = tracking_copy.get_contract(correlation_id, contract_hash)?;
+    let key = contract
+        .named_keys()
+        .get(name)
+        .copied()
+        .ok_or_else(|| execution::Error::NamedKeyNotFound(name.to_string()))?;
+    match tracking_copy
+        .read(correlation_id, &key)
+        .map_err(Into::<execution::Error>::into)?
+    {
+        Some(StoredValue::CLValue(cl_value)) => {
+            Ok(cl_value.into_t().map_err(execution::Error::from)?)
+        }
+        Some(other) => Err(execution::Error::TypeMismatch(StoredValueTypeMismatch::new(
+            "CLValue".to_string(),
+            other.type_name(),
+        ))
+        .into()),
+        None => Err(execution::Error::KeyNotFound(key).into()),
+    }
 }
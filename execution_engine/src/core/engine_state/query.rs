@@ -63,6 +63,33 @@ impl QueryRequest {
     }
 }
 
+/// Request for several global state queries against a single state root hash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchQueryRequest {
+    state_hash: Digest,
+    queries: Vec<(Key, Vec<String>)>,
+}
+
+impl BatchQueryRequest {
+    /// Creates new request object from `(key, path)` pairs.
+    pub fn new(state_hash: Digest, queries: Vec<(Key, Vec<String>)>) -> Self {
+        BatchQueryRequest {
+            state_hash,
+            queries,
+        }
+    }
+
+    /// Returns state root hash.
+    pub fn state_hash(&self) -> Digest {
+        self.state_hash
+    }
+
+    /// Returns the `(key, path)` pairs to query, in order.
+    pub fn queries(&self) -> &[(Key, Vec<String>)] {
+        &self.queries
+    }
+}
+
 impl From<TrackingCopyQueryResult> for QueryResult {
     fn from(tracking_copy_query_result: TrackingCopyQueryResult) -> Self {
         match tracking_copy_query_result {
@@ -78,3 +105,67 @@ impl From<TrackingCopyQueryResult> for QueryResult {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use casper_types::CLValue;
+
+    use super::*;
+    use crate::{
+        core::engine_state::{EngineConfig, EngineState},
+        shared::newtypes::CorrelationId,
+        storage::global_state::in_memory::InMemoryGlobalState,
+    };
+
+    fn value(n: u64) -> StoredValue {
+        StoredValue::CLValue(CLValue::from_t(n).unwrap())
+    }
+
+    #[test]
+    fn should_run_batch_query_in_order() {
+        let correlation_id = CorrelationId::new();
+        let (first, second, missing) = (Key::Hash([1; 32]), Key::Hash([2; 32]), Key::Hash([3; 32]));
+        let (global_state, state_hash) = InMemoryGlobalState::from_pairs(
+            correlation_id,
+            &[(first, value(1)), (second, value(2))],
+        )
+        .unwrap();
+        let engine_state = EngineState::new(global_state, EngineConfig::default());
+
+        let queries = vec![(second, vec![]), (missing, vec![]), (first, vec![])];
+        let results = engine_state
+            .run_batch_query(correlation_id, BatchQueryRequest::new(state_hash, queries))
+            .unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert!(
+            matches!(&results[0], QueryResult::Success { value: found, .. } if **found == value(2))
+        );
+        assert!(matches!(&results[1], QueryResult::ValueNotFound(_)));
+        assert!(
+            matches!(&results[2], QueryResult::Success { value: found, .. } if **found == value(1))
+        );
+    }
+
+    #[test]
+    fn should_report_unknown_root_for_every_query() {
+        let correlation_id = CorrelationId::new();
+        let key = Key::Hash([1; 32]);
+        let (global_state, _) =
+            InMemoryGlobalState::from_pairs(correlation_id, &[(key, value(1))]).unwrap();
+        let engine_state = EngineState::new(global_state, EngineConfig::default());
+
+        let queries = vec![(key, vec![]), (key, vec![])];
+        let results = engine_state
+            .run_batch_query(
+                correlation_id,
+                BatchQueryRequest::new(Digest::hash(&[1, 2, 3]), queries),
+            )
+            .unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert!(results
+            .iter()
+            .all(|result| matches!(result, QueryResult::RootNotFound)));
+    }
+}
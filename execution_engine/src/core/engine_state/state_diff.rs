@@ -0,0 +1,270 @@
+//! Support for finding the values which differ between two state roots.
+use std::collections::BTreeMap;
+
+use casper_hashing::Digest;
+use casper_types::{Key, StoredValue};
+
+use crate::{
+    core::engine_state::Error,
+    storage::trie::{Pointer, Trie, RADIX},
+};
+
+/// The values under a single key at two state roots.
+///
+/// A value of `None` means the key is absent at that state root.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValueChange {
+    /// The value at the pre state root.
+    pub before: Option<StoredValue>,
+    /// The value at the post state root.
+    pub after: Option<StoredValue>,
+}
+
+/// The keys whose values differ between two state roots.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StateDiff(BTreeMap<Key, ValueChange>);
+
+impl StateDiff {
+    /// Returns the changed values, keyed by the key they are stored under.
+    pub fn changes(&self) -> &BTreeMap<Key, ValueChange> {
+        &self.0
+    }
+
+    /// Returns the change of the value under `key`, if it changed.
+    pub fn get(&self, key: &Key) -> Option<&ValueChange> {
+        self.0.get(key)
+    }
+
+    /// Returns the number of changed values.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if both state roots hold the same values.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Consumes the diff, returning the changed values.
+    pub fn into_changes(self) -> BTreeMap<Key, ValueChange> {
+        self.0
+    }
+}
+
+/// Walks the tries under `pre_state_hash` and `post_state_hash` in step, skipping subtries which
+/// have the same hash under both roots.
+///
+/// Wherever the shape of the tries differs, all leaves under both subtries are collected and
+/// compared by key.
+pub(crate) struct StateDiffer<F> {
+    get_trie: F,
+    before: BTreeMap<Key, StoredValue>,
+    after: BTreeMap<Key, StoredValue>,
+}
+
+impl<F> StateDiffer<F>
+where
+    F: FnMut(&Digest) -> Result<Trie<Key, StoredValue>, Error>,
+{
+    /// Creates a differ which reads tries with `get_trie`.
+    pub(crate) fn new(get_trie: F) -> Self {
+        StateDiffer {
+            get_trie,
+            before: BTreeMap::new(),
+            after: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the values which differ between `pre_state_hash` and `post_state_hash`.
+    pub(crate) fn diff(
+        mut self,
+        pre_state_hash: Digest,
+        post_state_hash: Digest,
+    ) -> Result<StateDiff, Error> {
+        self.diff_subtries(Some(pre_state_hash), Some(post_state_hash))?;
+
+        let mut changes = BTreeMap::new();
+        for (key, before) in self.before {
+            let after = self.after.remove(&key);
+            if after.as_ref() != Some(&before) {
+                let before = Some(before);
+                changes.insert(key, ValueChange { before, after });
+            }
+        }
+        for (key, after) in self.after {
+            let after = Some(after);
+            changes.insert(
+                key,
+                ValueChange {
+                    before: None,
+                    after,
+                },
+            );
+        }
+        Ok(StateDiff(changes))
+    }
+
+    fn diff_subtries(&mut self, pre: Option<Digest>, post: Option<Digest>) -> Result<(), Error> {
+        if pre == post {
+            return Ok(());
+        }
+        let pre_trie = pre.map(|digest| (self.get_trie)(&digest)).transpose()?;
+        let post_trie = post.map(|digest| (self.get_trie)(&digest)).transpose()?;
+
+        match (pre_trie, post_trie) {
+            (
+                Some(Trie::Node {
+                    pointer_block: pre_pointer_block,
+                }),
+                Some(Trie::Node {
+                    pointer_block: post_pointer_block,
+                }),
+            ) => {
+                for index in 0..RADIX {
+                    let pre_child = pre_pointer_block[index].map(Pointer::into_hash);
+                    let post_child = post_pointer_block[index].map(Pointer::into_hash);
+                    self.diff_subtries(pre_child, post_child)?;
+                }
+                Ok(())
+            }
+            (
+                Some(Trie::Extension {
+                    affix: pre_affix,
+                    pointer: pre_pointer,
+                }),
+                Some(Trie::Extension {
+                    affix: post_affix,
+                    pointer: post_pointer,
+                }),
+            ) if pre_affix == post_affix => self.diff_subtries(
+                Some(pre_pointer.into_hash()),
+                Some(post_pointer.into_hash()),
+            ),
+            (pre_trie, post_trie) => {
+                if let Some(trie) = pre_trie {
+                    collect_leaves(&mut self.get_trie, trie, &mut self.before)?;
+                }
+                if let Some(trie) = post_trie {
+                    collect_leaves(&mut self.get_trie, trie, &mut self.after)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Collects all leaves under `trie` into `leaves`.
+fn collect_leaves<F>(
+    get_trie: &mut F,
+    trie: Trie<Key, StoredValue>,
+    leaves: &mut BTreeMap<Key, StoredValue>,
+) -> Result<(), Error>
+where
+    F: FnMut(&Digest) -> Result<Trie<Key, StoredValue>, Error>,
+{
+    let mut pending = vec![trie];
+    while let Some(trie) = pending.pop() {
+        match trie {
+            Trie::Leaf { key, value } => {
+                leaves.insert(key, value);
+            }
+            Trie::Node { pointer_block } => {
+                for (_, pointer) in pointer_block.as_indexed_pointers() {
+                    pending.push(get_trie(pointer.hash())?);
+                }
+            }
+            Trie::Extension { pointer, .. } => {
+                pending.push(get_trie(pointer.hash())?);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::CLValue;
+
+    use super::*;
+    use crate::{
+        core::engine_state::{EngineConfig, EngineState},
+        shared::{additive_map::AdditiveMap, newtypes::CorrelationId, transform::Transform},
+        storage::global_state::{in_memory::InMemoryGlobalState, StateProvider},
+    };
+
+    fn value(n: u64) -> StoredValue {
+        StoredValue::CLValue(CLValue::from_t(n).unwrap())
+    }
+
+    fn key(n: u8) -> Key {
+        Key::Hash([n; 32])
+    }
+
+    #[test]
+    fn should_diff_changed_and_added_values() {
+        let correlation_id = CorrelationId::new();
+        let pairs: Vec<(Key, StoredValue)> = (0..50).map(|n| (key(n), value(n as u64))).collect();
+        let (global_state, pre_state_hash) =
+            InMemoryGlobalState::from_pairs(correlation_id, &pairs).unwrap();
+
+        let mut effects = AdditiveMap::new();
+        effects.insert(key(7), Transform::Write(value(700)));
+        effects.insert(key(100), Transform::Write(value(100)));
+        // Writing an unchanged value doesn't show up in the diff.
+        effects.insert(key(8), Transform::Write(value(8)));
+        let post_state_hash = global_state
+            .commit(correlation_id, pre_state_hash, effects)
+            .unwrap();
+
+        let engine_state = EngineState::new(global_state, EngineConfig::default());
+        let state_diff = engine_state
+            .get_state_diff(correlation_id, pre_state_hash, post_state_hash)
+            .unwrap();
+
+        assert_eq!(state_diff.len(), 2);
+        assert_eq!(
+            state_diff.get(&key(7)),
+            Some(&ValueChange {
+                before: Some(value(7)),
+                after: Some(value(700)),
+            })
+        );
+        assert_eq!(
+            state_diff.get(&key(100)),
+            Some(&ValueChange {
+                before: None,
+                after: Some(value(100)),
+            })
+        );
+
+        let reverse_diff = engine_state
+            .get_state_diff(correlation_id, post_state_hash, pre_state_hash)
+            .unwrap();
+        assert_eq!(
+            reverse_diff.get(&key(100)),
+            Some(&ValueChange {
+                before: Some(value(100)),
+                after: None,
+            })
+        );
+
+        assert!(engine_state
+            .get_state_diff(correlation_id, pre_state_hash, pre_state_hash)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn should_fail_on_unknown_state_root() {
+        let correlation_id = CorrelationId::new();
+        let (global_state, state_hash) =
+            InMemoryGlobalState::from_pairs(correlation_id, &[(key(1), value(1))]).unwrap();
+        let engine_state = EngineState::new(global_state, EngineConfig::default());
+        let unknown_state_hash = Digest::hash(&[1, 2, 3]);
+
+        let error = engine_state
+            .get_state_diff(correlation_id, state_hash, unknown_state_hash)
+            .unwrap_err();
+        assert!(matches!(error, Error::RootNotFound(hash) if hash == unknown_state_hash));
+    }
+}
@@ -1,32 +1,198 @@
 //! Support for applying upgrades on the execution engine.
-use std::{cell::RefCell, collections::BTreeMap, fmt, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    ops::Deref,
+    rc::Rc,
+    sync::Arc,
+};
 
 use num_rational::Ratio;
+use serde::{Serialize, Serializer};
 use thiserror::Error;
+use tracing::{info_span, warn};
 
 use casper_hashing::Digest;
 use casper_types::{
-    bytesrepr,
+    bytesrepr::{self, ToBytes},
     system::{
         auction, handle_payment, mint, standard_payment, AUCTION, HANDLE_PAYMENT, MINT,
         STANDARD_PAYMENT,
     },
-    Contract, ContractHash, EntryPoints, EraId, Key, ProtocolVersion, StoredValue,
+    CLValue, Contract, ContractHash, ContractPackage, ContractPackageHash, ContractVersionKey,
+    EntryPoints, EraId, Key, ProtocolVersion, StoredValue, U512,
 };
 
 use crate::{
-    core::{engine_state::execution_effect::ExecutionEffect, tracking_copy::TrackingCopy},
-    shared::newtypes::CorrelationId,
-    storage::global_state::StateProvider,
+    core::{
+        engine_state::{execution_effect::ExecutionEffect, genesis::SystemContractRegistry},
+        execution,
+        tracking_copy::{TrackingCopy, TrackingCopyExt},
+    },
+    shared::{execution_journal::ExecutionJournal, newtypes::CorrelationId, transform::Transform},
+    storage::global_state::StateReader,
 };
 
+/// The largest number of validator slots an upgrade may set.
+pub const MAX_NEW_VALIDATOR_SLOTS: u32 = 10_000;
+
+/// Name of the config flag selecting the semantics of the genesis timestamp.
+pub const GENESIS_TIMESTAMP_SEMANTICS_FLAG: &str = "genesis_timestamp_semantics";
+
+/// The config flags an upgrade may set, see [`UpgradeConfig::new_config_flags`].
+pub const KNOWN_CONFIG_FLAGS: &[&str] = &[GENESIS_TIMESTAMP_SEMANTICS_FLAG];
+
+/// The mint entry points the execution engine calls directly, which an upgrade must keep.
+pub const MINT_REQUIRED_ENTRY_POINTS: &[&str] = &[mint::METHOD_CREATE, mint::METHOD_TRANSFER];
+
+/// The auction entry points the execution engine calls directly, which an upgrade must keep.
+pub const AUCTION_REQUIRED_ENTRY_POINTS: &[&str] = &[
+    auction::METHOD_RUN_AUCTION,
+    auction::METHOD_SLASH,
+    auction::METHOD_DISTRIBUTE,
+    auction::METHOD_GET_ERA_VALIDATORS,
+];
+
+/// The handle payment entry points the execution engine calls directly, which an upgrade must
+/// keep.
+pub const HANDLE_PAYMENT_REQUIRED_ENTRY_POINTS: &[&str] = &[
+    handle_payment::METHOD_FINALIZE_PAYMENT,
+    handle_payment::METHOD_GET_PAYMENT_PURSE,
+];
+
+/// The standard payment entry points the execution engine calls directly, which an upgrade must
+/// keep.
+pub const STANDARD_PAYMENT_REQUIRED_ENTRY_POINTS: &[&str] = &[standard_payment::METHOD_PAY];
+
+/// Upgrade paths which must be followed, as `(from, to)` pairs of protocol versions.
+///
+/// An upgrade from a protocol version listed here as the source of a path must be to one of the
+/// versions listed as its targets, so that intermediate versions aren't skipped. Upgrades from
+/// versions which aren't listed are unrestricted.
+///
+/// This is the default table of an `EngineState`, which can be replaced with
+/// `EngineState::with_supported_upgrade_paths`.
+pub const SUPPORTED_UPGRADE_PATHS: &[(ProtocolVersion, ProtocolVersion)] = &[];
+
+/// The state root hash an upgrade is applied on top of.
+///
+/// Kept distinct from [`PostStateHash`] so the two can't be mixed up when chaining upgrades:
+///
+/// ```compile_fail
+/// use casper_execution_engine::core::engine_state::upgrade::{PostStateHash, PreStateHash};
+/// use casper_hashing::Digest;
+///
+/// let post_state_hash = PostStateHash::from(Digest::hash(b"state"));
+/// let pre_state_hash: PreStateHash = post_state_hash;
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PreStateHash(Digest);
+
+impl From<Digest> for PreStateHash {
+    fn from(digest: Digest) -> Self {
+        PreStateHash(digest)
+    }
+}
+
+impl From<PreStateHash> for Digest {
+    fn from(pre_state_hash: PreStateHash) -> Self {
+        pre_state_hash.0
+    }
+}
+
+impl fmt::Display for PreStateHash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+/// The state root hash produced by applying an upgrade.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PostStateHash(Digest);
+
+impl From<Digest> for PostStateHash {
+    fn from(digest: Digest) -> Self {
+        PostStateHash(digest)
+    }
+}
+
+impl From<PostStateHash> for Digest {
+    fn from(post_state_hash: PostStateHash) -> Self {
+        post_state_hash.0
+    }
+}
+
+impl fmt::Display for PostStateHash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
 /// Represents a successfuly executed upgrade.
 #[derive(Debug, Clone)]
 pub struct UpgradeSuccess {
     /// New state root hash generated after effects were applied.
-    pub post_state_hash: Digest,
+    pub post_state_hash: PostStateHash,
     /// Effects of executing an upgrade request.
     pub execution_effect: ExecutionEffect,
+    /// Hash of the effects, see [`ExecutionEffect::hash`].
+    pub effect_hash: Digest,
+    /// Transforms of executing an upgrade request, in the order they were applied.
+    pub execution_journal: ExecutionJournal,
+}
+
+impl UpgradeSuccess {
+    /// Returns the transforms of the upgrade in the order they were applied.
+    ///
+    /// Unlike [`ExecutionEffect::transforms`], which holds a single combined transform per key,
+    /// this yields every transform, so replaying them in order reproduces the upgrade. The order
+    /// is the same on every run of the same upgrade.
+    pub fn applied_transforms(&self) -> impl Iterator<Item = &(Key, Transform)> {
+        self.execution_journal.iter()
+    }
+
+    /// Returns the part of the effects touching the system contracts in `registry` or their
+    /// contract packages.
+    ///
+    /// Packages are recognized through the contracts written by the upgrade, so this leaves out
+    /// e.g. global state update entries, or config values read and written via named keys.
+    pub fn system_contract_effect(&self, registry: &SystemContractRegistry) -> ExecutionEffect {
+        let contract_keys: BTreeSet<Key> = registry
+            .values()
+            .map(|contract_hash| Key::from(*contract_hash))
+            .collect();
+        let contract_package_keys: BTreeSet<Key> = self
+            .execution_effect
+            .transforms
+            .iter()
+            .filter_map(|(key, transform)| match transform {
+                Transform::Write(StoredValue::Contract(contract))
+                    if contract_keys.contains(key) =>
+                {
+                    Some(Key::from(contract.contract_package_hash()))
+                }
+                _ => None,
+            })
+            .collect();
+        let is_system_contract_key =
+            |key: &Key| contract_keys.contains(key) || contract_package_keys.contains(key);
+
+        let mut system_contract_effect = ExecutionEffect::default();
+        for (key, op) in self.execution_effect.ops.iter() {
+            if is_system_contract_key(key) {
+                system_contract_effect.ops.insert(*key, *op);
+            }
+        }
+        for (key, transform) in self.execution_effect.transforms.iter() {
+            if is_system_contract_key(key) {
+                system_contract_effect
+                    .transforms
+                    .insert(*key, transform.clone());
+            }
+        }
+        system_contract_effect
+    }
 }
 
 impl fmt::Display for UpgradeSuccess {
@@ -39,10 +205,48 @@ impl fmt::Display for UpgradeSuccess {
     }
 }
 
+/// An [`UpgradeConfig`] which passed [`UpgradeConfig::validate`], and can no longer be altered.
+///
+/// Upgrades can only be applied from a validated config, so an unvalidated one is rejected at
+/// compile time:
+///
+/// ```compile_fail
+/// use casper_execution_engine::{
+///     core::engine_state::{EngineState, UpgradeConfig},
+///     shared::newtypes::CorrelationId,
+///     storage::global_state::in_memory::InMemoryGlobalState,
+/// };
+///
+/// fn upgrade(engine_state: &EngineState<InMemoryGlobalState>, upgrade_config: UpgradeConfig) {
+///     let _ = engine_state.commit_upgrade(CorrelationId::new(), upgrade_config);
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidatedUpgradeConfig(UpgradeConfig);
+
+impl ValidatedUpgradeConfig {
+    /// Returns the validated config, which can then be altered again.
+    pub fn into_inner(self) -> UpgradeConfig {
+        self.0
+    }
+}
+
+impl Deref for ValidatedUpgradeConfig {
+    type Target = UpgradeConfig;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// A callback invoked with the result of each committed upgrade, see
+/// [`crate::core::engine_state::EngineState::register_upgrade_observer`].
+pub type UpgradeObserver = Arc<dyn Fn(&UpgradeSuccess) + Send + Sync>;
+
 /// Represents the configuration of a protocol upgrade.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct UpgradeConfig {
-    pre_state_hash: Digest,
+    pre_state_hash: PreStateHash,
     current_protocol_version: ProtocolVersion,
     new_protocol_version: ProtocolVersion,
     activation_point: Option<EraId>,
@@ -52,13 +256,18 @@ pub struct UpgradeConfig {
     new_round_seigniorage_rate: Option<Ratio<u64>>,
     new_unbonding_delay: Option<u64>,
     global_state_update: BTreeMap<Key, StoredValue>,
+    disabled_versions_retention: Option<u32>,
+    network_name: Option<String>,
+    new_config_flags: Option<BTreeMap<String, CLValue>>,
+    global_state_update_notes: BTreeMap<Key, String>,
+    strict_global_state_update: bool,
 }
 
 impl UpgradeConfig {
     /// Create new upgrade config.
     #[allow(clippy::too_many_arguments)]
     pub fn new(
-        pre_state_hash: Digest,
+        pre_state_hash: PreStateHash,
         current_protocol_version: ProtocolVersion,
         new_protocol_version: ProtocolVersion,
         activation_point: Option<EraId>,
@@ -68,6 +277,9 @@ impl UpgradeConfig {
         new_round_seigniorage_rate: Option<Ratio<u64>>,
         new_unbonding_delay: Option<u64>,
         global_state_update: BTreeMap<Key, StoredValue>,
+        disabled_versions_retention: Option<u32>,
+        network_name: Option<String>,
+        new_config_flags: Option<BTreeMap<String, CLValue>>,
     ) -> Self {
         UpgradeConfig {
             pre_state_hash,
@@ -80,11 +292,16 @@ impl UpgradeConfig {
             new_round_seigniorage_rate,
             new_unbonding_delay,
             global_state_update,
+            disabled_versions_retention,
+            network_name,
+            new_config_flags,
+            global_state_update_notes: BTreeMap::new(),
+            strict_global_state_update: false,
         }
     }
 
     /// Returns the current state root state hash
-    pub fn pre_state_hash(&self) -> Digest {
+    pub fn pre_state_hash(&self) -> PreStateHash {
         self.pre_state_hash
     }
 
@@ -133,18 +350,440 @@ impl UpgradeConfig {
         &self.global_state_update
     }
 
+    /// Returns the config flags to set, keyed by flag name, if specified.
+    ///
+    /// Each flag is written under its [`config_flag_key`]; only [`KNOWN_CONFIG_FLAGS`] are
+    /// accepted.
+    pub fn new_config_flags(&self) -> Option<&BTreeMap<String, CLValue>> {
+        self.new_config_flags.as_ref()
+    }
+
+    /// Sets the config flags to set.
+    pub fn with_new_config_flags(&mut self, new_config_flags: BTreeMap<String, CLValue>) {
+        self.new_config_flags = Some(new_config_flags);
+    }
+
+    /// Returns the human-readable justifications of entries in the global state update, keyed by
+    /// the entry's key.
+    ///
+    /// Notes are advisory: they're logged as their entries are applied, but never written to global
+    /// state, and are ignored when comparing or fingerprinting upgrade configs.
+    pub fn global_state_update_notes(&self) -> &BTreeMap<Key, String> {
+        &self.global_state_update_notes
+    }
+
+    /// Sets the justifications of entries in the global state update.
+    pub fn with_global_state_update_notes(
+        &mut self,
+        global_state_update_notes: BTreeMap<Key, String>,
+    ) {
+        self.global_state_update_notes = global_state_update_notes;
+    }
+
+    /// Returns `true` if the upgrade must be rejected when its global state update targets a
+    /// system contract or contract package, rather than only warning about it.
+    pub fn strict_global_state_update(&self) -> bool {
+        self.strict_global_state_update
+    }
+
+    /// Sets whether a global state update targeting a system contract or contract package
+    /// rejects the upgrade.
+    pub fn with_strict_global_state_update(&mut self, strict_global_state_update: bool) {
+        self.strict_global_state_update = strict_global_state_update;
+    }
+
+    /// Returns `true` if this is an emergency upgrade, i.e. one which directly modifies global
+    /// state via a non-empty global state update.
+    pub fn is_emergency(&self) -> bool {
+        !self.global_state_update.is_empty()
+    }
+
+    /// Returns `true` if applying this upgrade wouldn't change global state: the major version is
+    /// unchanged, so the system contracts aren't rewritten, no new config values are set and the
+    /// global state update is empty.
+    pub fn is_no_op(&self) -> bool {
+        !self
+            .current_protocol_version
+            .check_next_version(&self.new_protocol_version)
+            .is_major_version()
+            && self.new_validator_slots.is_none()
+            && self.new_auction_delay.is_none()
+            && self.new_locked_funds_period_millis.is_none()
+            && self.new_round_seigniorage_rate.is_none()
+            && self.new_unbonding_delay.is_none()
+            && self
+                .new_config_flags
+                .as_ref()
+                .map_or(true, BTreeMap::is_empty)
+            && self.global_state_update.is_empty()
+    }
+
+    /// Returns the number of disabled system contract versions to retain if pruning is enabled.
+    ///
+    /// `None` means disabled versions are never pruned.
+    pub fn disabled_versions_retention(&self) -> Option<u32> {
+        self.disabled_versions_retention
+    }
+
+    /// Returns the name of the network this upgrade is intended for, if any.
+    pub fn network_name(&self) -> Option<&str> {
+        self.network_name.as_deref()
+    }
+
+    /// Checks that this upgrade is intended for the network named `network_name`.
+    ///
+    /// Upgrades which don't name a network pass the check.
+    pub fn check_network_name(&self, network_name: &str) -> Result<(), ProtocolUpgradeError> {
+        match &self.network_name {
+            Some(expected) if expected != network_name => {
+                Err(ProtocolUpgradeError::NetworkMismatch {
+                    expected: expected.clone(),
+                    found: network_name.to_string(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Sets new pre state hash.
-    pub fn with_pre_state_hash(&mut self, pre_state_hash: Digest) {
+    pub fn with_pre_state_hash(&mut self, pre_state_hash: PreStateHash) {
         self.pre_state_hash = pre_state_hash;
     }
+
+    /// Returns `true` if this upgrade is applied on top of the post state of `previous`, i.e. if
+    /// the two can be applied one after the other.
+    pub fn chains_from(&self, previous: &UpgradeSuccess) -> bool {
+        Digest::from(self.pre_state_hash) == Digest::from(previous.post_state_hash)
+    }
+
+    /// Returns `true` if upgrading from the current to the new protocol version is allowed by
+    /// [`SUPPORTED_UPGRADE_PATHS`].
+    pub fn is_supported_path(&self) -> bool {
+        self.is_supported_path_in(SUPPORTED_UPGRADE_PATHS)
+    }
+
+    /// Returns `true` if upgrading from the current to the new protocol version is allowed by
+    /// `supported_upgrade_paths`, see [`SUPPORTED_UPGRADE_PATHS`].
+    pub fn is_supported_path_in(
+        &self,
+        supported_upgrade_paths: &[(ProtocolVersion, ProtocolVersion)],
+    ) -> bool {
+        let mut is_restricted = false;
+        for (from, to) in supported_upgrade_paths {
+            if *from == self.current_protocol_version {
+                if *to == self.new_protocol_version {
+                    return true;
+                }
+                is_restricted = true;
+            }
+        }
+        !is_restricted
+    }
+
+    /// Checks that the values set in this upgrade config are sane and consistent with each other,
+    /// returning the first problem found, or a copy of the config which can be applied.
+    ///
+    /// See [`UpgradeConfig::validate_all`] for the checks made.
+    pub fn validate(&self) -> Result<ValidatedUpgradeConfig, ProtocolUpgradeError> {
+        self.validate_all()
+            .map(|()| ValidatedUpgradeConfig(self.clone()))
+            .map_err(|mut errors| errors.swap_remove(0))
+    }
+
+    /// Checks that the values set in this upgrade config are sane and consistent with each other,
+    /// reporting every problem found.
+    ///
+    /// The new protocol version must be a valid successor of the current one, reached by a
+    /// supported upgrade path.
+    ///
+    /// A new validator slot count must be non-zero and at most [`MAX_NEW_VALIDATOR_SLOTS`].
+    ///
+    /// A new round seigniorage rate must not exceed 1.
+    ///
+    /// An unbonding delay which isn't strictly greater than the auction delay would let validators
+    /// unbond before their bids take effect, so such configs are rejected.
+    ///
+    /// Every config flag set must be one of the [`KNOWN_CONFIG_FLAGS`].
+    ///
+    /// Notes on keys absent from the global state update are reported as warnings.
+    pub fn validation_report(&self) -> UpgradeValidationReport {
+        let mut report = UpgradeValidationReport::default();
+
+        if self
+            .current_protocol_version
+            .check_next_version(&self.new_protocol_version)
+            .is_invalid()
+        {
+            report.push_error(ProtocolUpgradeError::InvalidUpgradeConfig);
+        }
+        if !self.is_supported_path() {
+            report.push_error(ProtocolUpgradeError::UnsupportedUpgradePath {
+                from: self.current_protocol_version,
+                to: self.new_protocol_version,
+            });
+        }
+        if let Some(new_validator_slots) = self.new_validator_slots {
+            if new_validator_slots == 0 || new_validator_slots > MAX_NEW_VALIDATOR_SLOTS {
+                report.push_error(ProtocolUpgradeError::InvalidUpgradeConfig);
+            }
+        }
+        if let Some(new_round_seigniorage_rate) = self.new_round_seigniorage_rate {
+            if new_round_seigniorage_rate > Ratio::from_integer(1) {
+                report.push_error(ProtocolUpgradeError::InvalidUpgradeConfig);
+            }
+        }
+        if let (Some(unbonding_delay), Some(auction_delay)) =
+            (self.new_unbonding_delay, self.new_auction_delay)
+        {
+            if unbonding_delay <= auction_delay {
+                report.push_error(ProtocolUpgradeError::InvalidUpgradeConfig);
+            }
+        }
+        if let Some(new_config_flags) = &self.new_config_flags {
+            for flag_name in new_config_flags.keys() {
+                if !KNOWN_CONFIG_FLAGS.contains(&flag_name.as_str()) {
+                    report.push_error(ProtocolUpgradeError::UnknownConfigFlag(flag_name.clone()));
+                }
+            }
+        }
+        for key in self.global_state_update_notes.keys() {
+            if !self.global_state_update.contains_key(key) {
+                report.push_warning(format!(
+                    "note for {} which isn't in the global state update",
+                    key
+                ));
+            }
+        }
+
+        report
+    }
+
+    /// Checks that the values set in this upgrade config are sane and consistent with each other,
+    /// returning every error found.
+    ///
+    /// See [`UpgradeConfig::validation_report`] for the checks made.
+    pub fn validate_all(&self) -> Result<(), Vec<ProtocolUpgradeError>> {
+        self.validation_report().into_result()
+    }
+
+    /// Returns `true` if `other` is equal to this upgrade config in every field except
+    /// `pre_state_hash`.
+    ///
+    /// Each node applies an upgrade against its own pre-state hash, so this is the comparison to
+    /// use when checking that two nodes were handed the same upgrade.
+    pub fn equivalent_ignoring_pre_state(&self, other: &Self) -> bool {
+        // Destructure self, so we can't miss any fields.
+        let UpgradeConfig {
+            pre_state_hash: _,
+            current_protocol_version,
+            new_protocol_version,
+            activation_point,
+            new_validator_slots,
+            new_auction_delay,
+            new_locked_funds_period_millis,
+            new_round_seigniorage_rate,
+            new_unbonding_delay,
+            global_state_update,
+            disabled_versions_retention,
+            network_name,
+            new_config_flags,
+            global_state_update_notes: _,
+            strict_global_state_update,
+        } = self;
+
+        *current_protocol_version == other.current_protocol_version
+            && *new_protocol_version == other.new_protocol_version
+            && *activation_point == other.activation_point
+            && *new_validator_slots == other.new_validator_slots
+            && *new_auction_delay == other.new_auction_delay
+            && *new_locked_funds_period_millis == other.new_locked_funds_period_millis
+            && *new_round_seigniorage_rate == other.new_round_seigniorage_rate
+            && *new_unbonding_delay == other.new_unbonding_delay
+            && *global_state_update == other.global_state_update
+            && *disabled_versions_retention == other.disabled_versions_retention
+            && *network_name == other.network_name
+            && *new_config_flags == other.new_config_flags
+            && *strict_global_state_update == other.strict_global_state_update
+    }
+
+    /// Returns a fingerprint of this upgrade config, which nodes can log and compare to confirm
+    /// they were handed the same upgrade.
+    ///
+    /// This is the hash of the `bytesrepr` serialization of every field except `pre_state_hash`,
+    /// as that differs between nodes, and the advisory `global_state_update_notes`.
+    pub fn fingerprint(&self) -> Result<Digest, bytesrepr::Error> {
+        // Destructure self, so we can't miss any fields.
+        let UpgradeConfig {
+            pre_state_hash: _,
+            current_protocol_version,
+            new_protocol_version,
+            activation_point,
+            new_validator_slots,
+            new_auction_delay,
+            new_locked_funds_period_millis,
+            new_round_seigniorage_rate,
+            new_unbonding_delay,
+            global_state_update,
+            disabled_versions_retention,
+            network_name,
+            new_config_flags,
+            global_state_update_notes: _,
+            strict_global_state_update,
+        } = self;
+
+        let mut bytes = Vec::new();
+        bytes.append(&mut current_protocol_version.to_bytes()?);
+        bytes.append(&mut new_protocol_version.to_bytes()?);
+        bytes.append(&mut activation_point.to_bytes()?);
+        bytes.append(&mut new_validator_slots.to_bytes()?);
+        bytes.append(&mut new_auction_delay.to_bytes()?);
+        bytes.append(&mut new_locked_funds_period_millis.to_bytes()?);
+        bytes.append(&mut new_round_seigniorage_rate.to_bytes()?);
+        bytes.append(&mut new_unbonding_delay.to_bytes()?);
+        bytes.append(&mut global_state_update.to_bytes()?);
+        bytes.append(&mut disabled_versions_retention.to_bytes()?);
+        bytes.append(&mut network_name.to_bytes()?);
+        bytes.append(&mut new_config_flags.to_bytes()?);
+        bytes.append(&mut strict_global_state_update.to_bytes()?);
+        Ok(Digest::hash(&bytes))
+    }
+
+    /// Returns the fields of this upgrade config as stable key/value pairs suitable for structured
+    /// logging.
+    ///
+    /// Optional fields render as `Some(value)` or `None`, and the global state update and its notes
+    /// are summarized by their lengths under the `global_state_update_len` and
+    /// `global_state_update_notes_len` keys.
+    pub fn to_log_fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("pre_state_hash", self.pre_state_hash.to_string()),
+            (
+                "current_protocol_version",
+                self.current_protocol_version.to_string(),
+            ),
+            (
+                "new_protocol_version",
+                self.new_protocol_version.to_string(),
+            ),
+            (
+                "activation_point",
+                format_optional(self.activation_point.map(|era_id| era_id.value())),
+            ),
+            (
+                "new_validator_slots",
+                format_optional(self.new_validator_slots),
+            ),
+            ("new_auction_delay", format_optional(self.new_auction_delay)),
+            (
+                "new_locked_funds_period_millis",
+                format_optional(self.new_locked_funds_period_millis),
+            ),
+            (
+                "new_round_seigniorage_rate",
+                format_optional(self.new_round_seigniorage_rate),
+            ),
+            (
+                "new_unbonding_delay",
+                format_optional(self.new_unbonding_delay),
+            ),
+            (
+                "global_state_update_len",
+                self.global_state_update.len().to_string(),
+            ),
+            (
+                "global_state_update_notes_len",
+                self.global_state_update_notes.len().to_string(),
+            ),
+            (
+                "strict_global_state_update",
+                self.strict_global_state_update.to_string(),
+            ),
+            (
+                "disabled_versions_retention",
+                format_optional(self.disabled_versions_retention),
+            ),
+            ("network_name", format_optional(self.network_name.as_ref())),
+            (
+                "new_config_flags",
+                format_optional(self.new_config_flags.as_ref().map(|flags| {
+                    flags
+                        .keys()
+                        .map(String::as_str)
+                        .collect::<Vec<_>>()
+                        .join(",")
+                })),
+            ),
+        ]
+    }
+}
+
+fn format_optional<T: fmt::Display>(value: Option<T>) -> String {
+    match value {
+        Some(value) => format!("Some({})", value),
+        None => "None".to_string(),
+    }
+}
+
+/// A tuning parameter which an upgrade would change relative to the value currently active in
+/// global state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigFieldChange {
+    /// Change of the number of validator slots.
+    ValidatorSlots {
+        /// Currently active value.
+        current: u32,
+        /// Value set by the upgrade.
+        new: u32,
+    },
+    /// Change of the auction delay.
+    AuctionDelay {
+        /// Currently active value.
+        current: u64,
+        /// Value set by the upgrade.
+        new: u64,
+    },
+    /// Change of the locked funds period.
+    LockedFundsPeriodMillis {
+        /// Currently active value.
+        current: u64,
+        /// Value set by the upgrade.
+        new: u64,
+    },
+    /// Change of the unbonding delay.
+    UnbondingDelay {
+        /// Currently active value.
+        current: u64,
+        /// Value set by the upgrade.
+        new: u64,
+    },
+    /// Change of the round seigniorage rate.
+    RoundSeigniorageRate {
+        /// Currently active value.
+        current: Ratio<U512>,
+        /// Value set by the upgrade.
+        new: Ratio<U512>,
+    },
 }
 
 /// Represents outcomes of a failed protocol upgrade.
-#[derive(Clone, Error, Debug)]
+///
+/// Serializes with the variant's name in snake case under `kind`, and its fields, if any, under
+/// `details`.
+#[derive(Clone, Error, Debug, Serialize)]
+#[serde(tag = "kind", content = "details", rename_all = "snake_case")]
 pub enum ProtocolUpgradeError {
     /// Error validating a protocol upgrade config.
     #[error("Invalid upgrade config")]
     InvalidUpgradeConfig,
+    /// The system contract registry is absent from global state.
+    #[error(
+        "System contract registry is missing; it must be created (e.g. via the global state \
+         update) before system contracts can be upgraded"
+    )]
+    MissingSystemContractRegistry,
+    /// Unable to retrieve the system contract registry.
+    #[error("Unable to retrieve system contract registry: {0}")]
+    UnableToRetrieveSystemContractRegistry(String),
     /// Unable to retrieve a system contract.
     #[error("Unable to retrieve system contract: {0}")]
     UnableToRetrieveSystemContract(String),
@@ -156,10 +795,77 @@ pub enum ProtocolUpgradeError {
     FailedToDisablePreviousVersion(String),
     /// (De)serialization error.
     #[error("{0}")]
-    Bytesrepr(bytesrepr::Error),
+    Bytesrepr(#[serde(serialize_with = "serialize_as_string")] bytesrepr::Error),
     /// Failed to create system contract registry.
     #[error("Failed to insert system contract registry")]
     FailedToCreateSystemRegistry,
+    /// A migration failed to transform a stored value.
+    #[error("Migration failed: {0}")]
+    FailedMigration(String),
+    /// The entry points given for a system contract are empty.
+    #[error("Empty entry points for system contract: {0}")]
+    EmptyEntryPoints(String),
+    /// The upgrade is intended for a different network.
+    #[error("Upgrade is for network {expected}, not {found}")]
+    NetworkMismatch {
+        /// The network named in the upgrade config.
+        expected: String,
+        /// The network the upgrade was checked against.
+        found: String,
+    },
+    /// The upgrade sets a config flag which isn't one of the [`KNOWN_CONFIG_FLAGS`].
+    #[error("Unknown config flag: {0}")]
+    UnknownConfigFlag(String),
+    /// A system contract package was left without an enabled version.
+    #[error("No enabled versions of system contract: {0}")]
+    NoEnabledVersions(String),
+    /// The upgrade skips a protocol version which must be upgraded through.
+    #[error("Unsupported upgrade path from {from} to {to}")]
+    UnsupportedUpgradePath {
+        /// The current protocol version.
+        from: ProtocolVersion,
+        /// The new protocol version.
+        to: ProtocolVersion,
+    },
+    /// A system contract's recorded package doesn't list the contract as one of its versions.
+    #[error(
+        "Package hash mismatch for system contract {contract}: package {package_hash} doesn't \
+        list {contract_hash}"
+    )]
+    PackageHashMismatch {
+        /// The name of the system contract.
+        contract: String,
+        /// The hash of the system contract.
+        contract_hash: ContractHash,
+        /// The package hash recorded by the system contract.
+        package_hash: ContractPackageHash,
+    },
+    /// The entry points for a system contract lack one the execution engine relies on.
+    #[error("System contract {contract} is missing required entry point {entry_point}")]
+    MissingRequiredEntryPoint {
+        /// The name of the system contract.
+        contract: String,
+        /// The name of the missing entry point.
+        entry_point: String,
+    },
+    /// The global state update of a strict upgrade writes to a system contract or contract package.
+    #[error("Global state update targets system contract key {0}")]
+    UpdateTargetsSystemContract(Key),
+    /// A system contract is already stored with a newer protocol version than the upgrade's.
+    #[error("Stored system contract version {stored} is newer than target version {target}")]
+    StoredVersionNewerThanTarget {
+        /// The protocol version of the stored system contract.
+        stored: ProtocolVersion,
+        /// The new protocol version of the upgrade.
+        target: ProtocolVersion,
+    },
+}
+
+fn serialize_as_string<S: Serializer>(
+    error: &bytesrepr::Error,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(error)
 }
 
 impl From<bytesrepr::Error> for ProtocolUpgradeError {
@@ -168,27 +874,151 @@ impl From<bytesrepr::Error> for ProtocolUpgradeError {
     }
 }
 
+/// A programmable transformation of stored values, run as part of a protocol upgrade.
+///
+/// Migrations cover changes which can't be expressed as a static global state update, such as
+/// re-encoding an existing stored value.
+pub trait Migration {
+    /// Returns the keys whose stored values are transformed by this migration.
+    fn keys(&self) -> Vec<Key>;
+
+    /// Returns the migrated form of `value`, which is currently stored under `key`.
+    fn migrate(&self, key: &Key, value: StoredValue) -> Result<StoredValue, ProtocolUpgradeError>;
+}
+
+/// Provides the entry points installed for each system contract on a major version upgrade.
+pub trait EntryPointsProvider {
+    /// Returns the entry points of the mint contract.
+    fn mint_entry_points(&self) -> EntryPoints;
+    /// Returns the entry points of the auction contract.
+    fn auction_entry_points(&self) -> EntryPoints;
+    /// Returns the entry points of the handle payment contract.
+    fn handle_payment_entry_points(&self) -> EntryPoints;
+    /// Returns the entry points of the standard payment contract.
+    fn standard_payment_entry_points(&self) -> EntryPoints;
+}
+
+/// Provides the canonical entry points of the system contracts.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultEntryPointsProvider;
+
+impl EntryPointsProvider for DefaultEntryPointsProvider {
+    fn mint_entry_points(&self) -> EntryPoints {
+        mint::mint_entry_points()
+    }
+
+    fn auction_entry_points(&self) -> EntryPoints {
+        auction::auction_entry_points()
+    }
+
+    fn handle_payment_entry_points(&self) -> EntryPoints {
+        handle_payment::handle_payment_entry_points()
+    }
+
+    fn standard_payment_entry_points(&self) -> EntryPoints {
+        standard_payment::standard_payment_entry_points()
+    }
+}
+
+/// The problems found while validating an [`UpgradeConfig`].
+///
+/// Errors make the config invalid, while warnings only flag something worth a second look.
+#[derive(Clone, Debug, Default)]
+pub struct UpgradeValidationReport {
+    errors: Vec<ProtocolUpgradeError>,
+    warnings: Vec<String>,
+}
+
+impl UpgradeValidationReport {
+    /// Records an error.
+    pub fn push_error(&mut self, error: ProtocolUpgradeError) {
+        self.errors.push(error);
+    }
+
+    /// Records a warning.
+    pub fn push_warning(&mut self, warning: String) {
+        self.warnings.push(warning);
+    }
+
+    /// Returns the errors recorded so far.
+    pub fn errors(&self) -> &[ProtocolUpgradeError] {
+        &self.errors
+    }
+
+    /// Returns the warnings recorded so far.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Returns `true` if no errors have been recorded, regardless of any warnings.
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Returns `Ok` if no errors have been recorded, or all errors otherwise.
+    ///
+    /// Warnings are dropped.
+    pub fn into_result(self) -> Result<(), Vec<ProtocolUpgradeError>> {
+        if self.is_ok() {
+            Ok(())
+        } else {
+            Err(self.errors)
+        }
+    }
+}
+
+/// An estimate of the global state accesses made by upgrading the system contracts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UpgradeCostEstimate {
+    /// Number of global state reads.
+    pub reads: usize,
+    /// Number of system contracts written.
+    pub contracts: usize,
+    /// Number of system contract packages written.
+    pub contract_packages: usize,
+}
+
+impl UpgradeCostEstimate {
+    /// Returns the number of global state entries written, including the
+    /// `global_state_update_len` entries of the upgrade's global state update.
+    pub fn writes(&self, global_state_update_len: usize) -> usize {
+        self.contracts + self.contract_packages + global_state_update_len
+    }
+}
+
 /// The system upgrader deals with conducting an actual protocol upgrade.
-pub(crate) struct SystemUpgrader<S>
+pub(crate) struct SystemUpgrader<R>
 where
-    S: StateProvider,
+    R: StateReader<Key, StoredValue>,
 {
     new_protocol_version: ProtocolVersion,
-    tracking_copy: Rc<RefCell<TrackingCopy<<S as StateProvider>::Reader>>>,
+    disabled_versions_retention: Option<u32>,
+    max_read_attempts: u32,
+    tracking_copy: Rc<RefCell<TrackingCopy<R>>>,
+    entry_points_provider: Box<dyn EntryPointsProvider>,
 }
 
-impl<S> SystemUpgrader<S>
+impl<R> SystemUpgrader<R>
 where
-    S: StateProvider,
+    R: StateReader<Key, StoredValue>,
 {
     /// Creates new system upgrader instance.
+    ///
+    /// System contracts get the canonical entry points unless an `entry_points_provider` is given.
     pub(crate) fn new(
         new_protocol_version: ProtocolVersion,
-        tracking_copy: Rc<RefCell<TrackingCopy<<S as StateProvider>::Reader>>>,
+        disabled_versions_retention: Option<u32>,
+        max_read_attempts: u32,
+        tracking_copy: Rc<RefCell<TrackingCopy<R>>>,
+        entry_points_provider: Option<Box<dyn EntryPointsProvider>>,
     ) -> Self {
         SystemUpgrader {
             new_protocol_version,
+            disabled_versions_retention,
+            max_read_attempts,
             tracking_copy,
+            entry_points_provider: entry_points_provider
+                .unwrap_or_else(|| Box::new(DefaultEntryPointsProvider)),
         }
     }
 
@@ -201,29 +1031,117 @@ where
         handle_payment_hash: &ContractHash,
         standard_payment_hash: &ContractHash,
     ) -> Result<(), ProtocolUpgradeError> {
-        self.store_contract(correlation_id, *mint_hash, MINT, mint::mint_entry_points())?;
-        self.store_contract(
-            correlation_id,
-            *auction_hash,
+        // Check up front, so a state predating the registry isn't reported as a missing contract.
+        let registry_key = Key::SystemContractRegistry;
+        if self
+            .read_with_retry(correlation_id, &registry_key)
+            .map_err(|_| {
+                ProtocolUpgradeError::UnableToRetrieveSystemContractRegistry(
+                    registry_key.to_string(),
+                )
+            })?
+            .is_none()
+        {
+            return Err(ProtocolUpgradeError::MissingSystemContractRegistry);
+        }
+
+        info_span!(
+            MINT,
+            contract_hash = %mint_hash,
+            target_version = %self.new_protocol_version
+        )
+        .in_scope(|| {
+            self.store_contract(
+                correlation_id,
+                *mint_hash,
+                MINT,
+                self.entry_points_provider.mint_entry_points(),
+            )
+        })?;
+        info_span!(
             AUCTION,
-            auction::auction_entry_points(),
-        )?;
-        self.store_contract(
-            correlation_id,
-            *handle_payment_hash,
+            contract_hash = %auction_hash,
+            target_version = %self.new_protocol_version
+        )
+        .in_scope(|| {
+            self.store_contract(
+                correlation_id,
+                *auction_hash,
+                AUCTION,
+                self.entry_points_provider.auction_entry_points(),
+            )
+        })?;
+        info_span!(
             HANDLE_PAYMENT,
-            handle_payment::handle_payment_entry_points(),
-        )?;
-        self.store_contract(
-            correlation_id,
-            *standard_payment_hash,
+            contract_hash = %handle_payment_hash,
+            target_version = %self.new_protocol_version
+        )
+        .in_scope(|| {
+            self.store_contract(
+                correlation_id,
+                *handle_payment_hash,
+                HANDLE_PAYMENT,
+                self.entry_points_provider.handle_payment_entry_points(),
+            )
+        })?;
+        info_span!(
             STANDARD_PAYMENT,
-            standard_payment::standard_payment_entry_points(),
-        )?;
+            contract_hash = %standard_payment_hash,
+            target_version = %self.new_protocol_version
+        )
+        .in_scope(|| {
+            self.store_contract(
+                correlation_id,
+                *standard_payment_hash,
+                STANDARD_PAYMENT,
+                self.entry_points_provider.standard_payment_entry_points(),
+            )
+        })?;
 
         Ok(())
     }
 
+    /// Estimates the cost of [`SystemUpgrader::upgrade_system_contracts_major_version`] by
+    /// reading the system contracts and packages it would touch, without writing anything.
+    pub(crate) fn estimate_cost(
+        &self,
+        correlation_id: CorrelationId,
+        mint_hash: &ContractHash,
+        auction_hash: &ContractHash,
+        handle_payment_hash: &ContractHash,
+        standard_payment_hash: &ContractHash,
+    ) -> Result<UpgradeCostEstimate, ProtocolUpgradeError> {
+        let registry_key = Key::SystemContractRegistry;
+        if self
+            .read_with_retry(correlation_id, &registry_key)
+            .map_err(|_| {
+                ProtocolUpgradeError::UnableToRetrieveSystemContractRegistry(
+                    registry_key.to_string(),
+                )
+            })?
+            .is_none()
+        {
+            return Err(ProtocolUpgradeError::MissingSystemContractRegistry);
+        }
+
+        let mut estimate = UpgradeCostEstimate {
+            reads: 1,
+            ..Default::default()
+        };
+        for (contract_hash, contract_name) in [
+            (mint_hash, MINT),
+            (auction_hash, AUCTION),
+            (handle_payment_hash, HANDLE_PAYMENT),
+            (standard_payment_hash, STANDARD_PAYMENT),
+        ] {
+            self.read_contract_and_package(correlation_id, *contract_hash, contract_name)?;
+            estimate.reads += 2;
+            estimate.contracts += 1;
+            estimate.contract_packages += 1;
+        }
+        Ok(estimate)
+    }
+
     /// Store new system contract.
     fn store_contract(
         &self,
@@ -232,12 +1150,70 @@ where
         contract_name: &str,
         entry_points: EntryPoints,
     ) -> Result<(), ProtocolUpgradeError> {
-        let contract_key = Key::Hash(contract_hash.value());
+        if entry_points.keys().next().is_none() {
+            return Err(ProtocolUpgradeError::EmptyEntryPoints(
+                contract_name.to_string(),
+            ));
+        }
+        check_required_entry_points(&entry_points, contract_name)?;
+
+        let (mut contract, mut contract_package) =
+            self.read_contract_and_package(correlation_id, contract_hash, contract_name)?;
+        check_package_lists_contract(&contract, &contract_package, contract_hash, contract_name)?;
+        // A newer stored contract means the state was written by a newer node.
+        if contract.protocol_version() > self.new_protocol_version {
+            return Err(ProtocolUpgradeError::StoredVersionNewerThanTarget {
+                stored: contract.protocol_version(),
+                target: self.new_protocol_version,
+            });
+        }
+
+        contract_package
+            .disable_contract_version(contract_hash)
+            .map_err(|_| {
+                ProtocolUpgradeError::FailedToDisablePreviousVersion(contract_name.to_string())
+            })?;
+        contract.set_protocol_version(self.new_protocol_version);
 
-        let mut contract = if let StoredValue::Contract(contract) = self
-            .tracking_copy
+        let new_contract = Contract::new(
+            contract.contract_package_hash(),
+            contract.contract_wasm_hash(),
+            contract.named_keys().clone(),
+            entry_points,
+            self.new_protocol_version,
+        );
+        self.tracking_copy
             .borrow_mut()
-            .read(correlation_id, &contract_key)
+            .write(contract_hash.into(), StoredValue::Contract(new_contract));
+
+        contract_package
+            .insert_contract_version(self.new_protocol_version.value().major, contract_hash);
+
+        if let Some(retention) = self.disabled_versions_retention {
+            prune_disabled_versions(&mut contract_package, retention as usize);
+        }
+
+        check_enabled_versions(&contract_package, contract_name)?;
+
+        self.tracking_copy.borrow_mut().write(
+            Key::Hash(contract.contract_package_hash().value()),
+            StoredValue::ContractPackage(contract_package),
+        );
+
+        Ok(())
+    }
+
+    /// Reads the system contract stored under `contract_hash` and its contract package.
+    fn read_contract_and_package(
+        &self,
+        correlation_id: CorrelationId,
+        contract_hash: ContractHash,
+        contract_name: &str,
+    ) -> Result<(Contract, ContractPackage), ProtocolUpgradeError> {
+        let contract_key = Key::Hash(contract_hash.value());
+
+        let contract = if let StoredValue::Contract(contract) = self
+            .read_with_retry(correlation_id, &contract_key)
             .map_err(|_| {
                 ProtocolUpgradeError::UnableToRetrieveSystemContract(contract_name.to_string())
             })?
@@ -253,10 +1229,8 @@ where
 
         let contract_package_key = Key::Hash(contract.contract_package_hash().value());
 
-        let mut contract_package = if let StoredValue::ContractPackage(contract_package) = self
-            .tracking_copy
-            .borrow_mut()
-            .read(correlation_id, &contract_package_key)
+        let contract_package = if let StoredValue::ContractPackage(contract_package) = self
+            .read_with_retry(correlation_id, &contract_package_key)
             .map_err(|_| {
                 ProtocolUpgradeError::UnableToRetrieveSystemContractPackage(
                     contract_name.to_string(),
@@ -274,32 +1248,1275 @@ where
             ));
         };
 
-        contract_package
-            .disable_contract_version(contract_hash)
-            .map_err(|_| {
-                ProtocolUpgradeError::FailedToDisablePreviousVersion(contract_name.to_string())
-            })?;
-        contract.set_protocol_version(self.new_protocol_version);
+        Ok((contract, contract_package))
+    }
 
-        let new_contract = Contract::new(
-            contract.contract_package_hash(),
-            contract.contract_wasm_hash(),
-            contract.named_keys().clone(),
-            entry_points,
-            self.new_protocol_version,
+    /// Reads the value under `key`, retrying failed reads up to `max_read_attempts` times in total.
+    ///
+    /// A missing value is not retried, as it can't be caused by a transient failure.
+    fn read_with_retry(
+        &self,
+        correlation_id: CorrelationId,
+        key: &Key,
+    ) -> Result<Option<StoredValue>, R::Error> {
+        let mut attempt = 1;
+        loop {
+            match self.tracking_copy.borrow_mut().read(correlation_id, key) {
+                Err(_) if attempt < self.max_read_attempts => {
+                    warn!(%key, attempt, "failed to read system contract state, retrying");
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Returns the key under which the config flag named `flag_name` is stored.
+pub fn config_flag_key(flag_name: &str) -> Key {
+    Key::Hash(Digest::hash(format!("config_flag:{}", flag_name)).value())
+}
+
+/// Returns the system contract registry a major version upgrade produces from `current`, without
+/// touching global state.
+///
+/// System contracts are upgraded in place: each new contract version is stored under the hash of
+/// the contract it replaces, so the registry is left as it was.
+pub fn predicted_system_contract_registry(
+    current: &SystemContractRegistry,
+) -> SystemContractRegistry {
+    current.clone()
+}
+
+/// Returns the name under which `contract_hash` is registered in the system contract registry, or
+/// `None` if it isn't a system contract.
+pub(crate) fn system_contract_name_for<R>(
+    tracking_copy: &mut TrackingCopy<R>,
+    correlation_id: CorrelationId,
+    contract_hash: ContractHash,
+) -> Result<Option<String>, ProtocolUpgradeError>
+where
+    R: StateReader<Key, StoredValue>,
+    R::Error: Into<execution::Error>,
+{
+    let registry = tracking_copy
+        .get_system_contracts(correlation_id)
+        .map_err(|error| {
+            ProtocolUpgradeError::UnableToRetrieveSystemContractRegistry(error.to_string())
+        })?;
+    Ok(registry
+        .into_iter()
+        .find(|(_, registered_hash)| *registered_hash == contract_hash)
+        .map(|(name, _)| name))
+}
+
+/// Checks that `contract_package` still has an enabled version, as a system contract without one
+/// can no longer be called.
+fn check_enabled_versions(
+    contract_package: &ContractPackage,
+    contract_name: &str,
+) -> Result<(), ProtocolUpgradeError> {
+    if contract_package.enabled_versions().is_empty() {
+        return Err(ProtocolUpgradeError::NoEnabledVersions(
+            contract_name.to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the entry points which the given system contract must keep across upgrades.
+fn required_entry_points(contract_name: &str) -> &'static [&'static str] {
+    match contract_name {
+        MINT => MINT_REQUIRED_ENTRY_POINTS,
+        AUCTION => AUCTION_REQUIRED_ENTRY_POINTS,
+        HANDLE_PAYMENT => HANDLE_PAYMENT_REQUIRED_ENTRY_POINTS,
+        STANDARD_PAYMENT => STANDARD_PAYMENT_REQUIRED_ENTRY_POINTS,
+        _ => &[],
+    }
+}
+
+/// Checks that `entry_points` contains every entry point required of the named system contract.
+fn check_required_entry_points(
+    entry_points: &EntryPoints,
+    contract_name: &str,
+) -> Result<(), ProtocolUpgradeError> {
+    match required_entry_points(contract_name)
+        .iter()
+        .find(|entry_point| !entry_points.has_entry_point(entry_point))
+    {
+        Some(entry_point) => Err(ProtocolUpgradeError::MissingRequiredEntryPoint {
+            contract: contract_name.to_string(),
+            entry_point: entry_point.to_string(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Checks that `contract_package`, read under the package hash recorded by `contract`, lists
+/// `contract_hash` as one of its versions.
+///
+/// A package which doesn't list the contract means the contract records the wrong package hash,
+/// i.e. global state is corrupt.
+fn check_package_lists_contract(
+    contract: &Contract,
+    contract_package: &ContractPackage,
+    contract_hash: ContractHash,
+    contract_name: &str,
+) -> Result<(), ProtocolUpgradeError> {
+    if !contract_package
+        .versions()
+        .values()
+        .any(|version_hash| *version_hash == contract_hash)
+    {
+        return Err(ProtocolUpgradeError::PackageHashMismatch {
+            contract: contract_name.to_string(),
+            contract_hash,
+            package_hash: contract.contract_package_hash(),
+        });
+    }
+    Ok(())
+}
+
+/// Removes all but the `retention` newest disabled versions from the given contract package.
+fn prune_disabled_versions(contract_package: &mut ContractPackage, retention: usize) {
+    let pruned_versions: Vec<ContractVersionKey> = contract_package
+        .disabled_versions()
+        .iter()
+        .rev()
+        .skip(retention)
+        .copied()
+        .collect();
+    for contract_version_key in pruned_versions {
+        contract_package
+            .versions_mut()
+            .remove(&contract_version_key);
+        contract_package
+            .disabled_versions_mut()
+            .remove(&contract_version_key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::Cell,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc, Mutex,
+        },
+    };
+
+    use assert_matches::assert_matches;
+    use tracing::{
+        span::{Attributes, Id, Record},
+        Event, Metadata, Subscriber,
+    };
+
+    use casper_types::{
+        contracts::{ContractVersions, NamedKeys},
+        CLValue, ContractWasmHash, EntryPoint, HashAddr,
+    };
+
+    use super::*;
+    use crate::{
+        core::engine_state::engine_config::DEFAULT_MAX_UPGRADE_READ_ATTEMPTS,
+        storage::{
+            global_state::{
+                in_memory::{InMemoryGlobalState, InMemoryGlobalStateView},
+                StateProvider,
+            },
+            trie::merkle_proof::TrieMerkleProof,
+        },
+    };
+
+    const CONTRACT_HASH: ContractHash = ContractHash::new([1; 32]);
+    const CONTRACT_PACKAGE_HASH: ContractPackageHash = ContractPackageHash::new([2; 32]);
+
+    type TestTrackingCopy = Rc<RefCell<TrackingCopy<InMemoryGlobalStateView>>>;
+
+    /// Reader which fails a given number of reads before delegating to the wrapped view.
+    struct FlakyReader {
+        view: InMemoryGlobalStateView,
+        failures: Cell<u32>,
+    }
+
+    impl StateReader<Key, StoredValue> for FlakyReader {
+        type Error = String;
+
+        fn read(
+            &self,
+            correlation_id: CorrelationId,
+            key: &Key,
+        ) -> Result<Option<StoredValue>, Self::Error> {
+            let failures = self.failures.get();
+            if failures > 0 {
+                self.failures.set(failures - 1);
+                return Err("transient read failure".to_string());
+            }
+            self.view
+                .read(correlation_id, key)
+                .map_err(|error| error.to_string())
+        }
+
+        fn read_with_proof(
+            &self,
+            _correlation_id: CorrelationId,
+            _key: &Key,
+        ) -> Result<Option<TrieMerkleProof<Key, StoredValue>>, Self::Error> {
+            Ok(None)
+        }
+
+        fn keys_with_prefix(
+            &self,
+            _correlation_id: CorrelationId,
+            _prefix: &[u8],
+        ) -> Result<Vec<Key>, Self::Error> {
+            Ok(Vec::new())
+        }
+    }
+
+    /// Subscriber which records the names of all spans created while it is the default.
+    struct SpanRecorder {
+        span_names: Arc<Mutex<Vec<&'static str>>>,
+        next_id: AtomicU64,
+    }
+
+    impl Subscriber for SpanRecorder {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, attributes: &Attributes<'_>) -> Id {
+            self.span_names
+                .lock()
+                .unwrap()
+                .push(attributes.metadata().name());
+            Id::from_u64(self.next_id.fetch_add(1, Ordering::SeqCst))
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, _event: &Event<'_>) {}
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+    }
+
+    fn make_contract() -> Contract {
+        Contract::new(
+            CONTRACT_PACKAGE_HASH,
+            ContractWasmHash::new([0; 32]),
+            NamedKeys::new(),
+            EntryPoints::new(),
+            ProtocolVersion::V1_0_0,
+        )
+    }
+
+    fn make_flaky_tracking_copy(failures: u32) -> Rc<RefCell<TrackingCopy<FlakyReader>>> {
+        let (global_state, root_hash) = InMemoryGlobalState::from_pairs(
+            CorrelationId::new(),
+            &[
+                (CONTRACT_HASH.into(), StoredValue::Contract(make_contract())),
+                (
+                    CONTRACT_PACKAGE_HASH.into(),
+                    StoredValue::ContractPackage(make_contract_package(&[])),
+                ),
+            ],
+        )
+        .expect("should create global state");
+        let view = global_state
+            .checkout(root_hash)
+            .expect("should checkout")
+            .expect("should have root");
+        let reader = FlakyReader {
+            view,
+            failures: Cell::new(failures),
+        };
+        Rc::new(RefCell::new(TrackingCopy::new(reader)))
+    }
+
+    fn make_tracking_copy(contract_package: ContractPackage) -> TestTrackingCopy {
+        let global_state = InMemoryGlobalState::empty().expect("should create global state");
+        let reader = global_state
+            .checkout(global_state.empty_root_hash())
+            .expect("should checkout")
+            .expect("should have empty root");
+        let mut tracking_copy = TrackingCopy::new(reader);
+
+        tracking_copy.write(CONTRACT_HASH.into(), StoredValue::Contract(make_contract()));
+        tracking_copy.write(
+            CONTRACT_PACKAGE_HASH.into(),
+            StoredValue::ContractPackage(contract_package),
         );
-        self.tracking_copy
+
+        Rc::new(RefCell::new(tracking_copy))
+    }
+
+    fn write_registry(tracking_copy: &TestTrackingCopy, entries: &[(&str, ContractHash)]) {
+        let registry: BTreeMap<String, ContractHash> = entries
+            .iter()
+            .map(|(name, contract_hash)| (name.to_string(), *contract_hash))
+            .collect();
+        tracking_copy.borrow_mut().write(
+            Key::SystemContractRegistry,
+            StoredValue::CLValue(CLValue::from_t(registry).unwrap()),
+        );
+    }
+
+    fn make_contract_package(disabled: &[HashAddr]) -> ContractPackage {
+        let mut contract_package = ContractPackage::default();
+        for hash_addr in disabled {
+            let contract_hash = ContractHash::new(*hash_addr);
+            contract_package.insert_contract_version(1, contract_hash);
+            contract_package
+                .disable_contract_version(contract_hash)
+                .expect("should disable");
+        }
+        contract_package.insert_contract_version(1, CONTRACT_HASH);
+        contract_package
+    }
+
+    fn read_contract_package(tracking_copy: &TestTrackingCopy) -> ContractPackage {
+        match tracking_copy
             .borrow_mut()
-            .write(contract_hash.into(), StoredValue::Contract(new_contract));
+            .read(CorrelationId::new(), &CONTRACT_PACKAGE_HASH.into())
+        {
+            Ok(Some(StoredValue::ContractPackage(contract_package))) => contract_package,
+            other => panic!("expected contract package, got {:?}", other),
+        }
+    }
+
+    fn upgrade_contract(
+        tracking_copy: &TestTrackingCopy,
+        new_protocol_version: ProtocolVersion,
+        disabled_versions_retention: Option<u32>,
+    ) -> Result<(), ProtocolUpgradeError> {
+        upgrade_contract_at(
+            tracking_copy,
+            CONTRACT_HASH,
+            new_protocol_version,
+            disabled_versions_retention,
+        )
+    }
+
+    fn upgrade_contract_at(
+        tracking_copy: &TestTrackingCopy,
+        contract_hash: ContractHash,
+        new_protocol_version: ProtocolVersion,
+        disabled_versions_retention: Option<u32>,
+    ) -> Result<(), ProtocolUpgradeError> {
+        let system_upgrader: SystemUpgrader<InMemoryGlobalStateView> = SystemUpgrader::new(
+            new_protocol_version,
+            disabled_versions_retention,
+            DEFAULT_MAX_UPGRADE_READ_ATTEMPTS,
+            Rc::clone(tracking_copy),
+            None,
+        );
+        system_upgrader.store_contract(
+            CorrelationId::new(),
+            contract_hash,
+            MINT,
+            mint::mint_entry_points(),
+        )
+    }
+
+    fn make_upgrade_config() -> UpgradeConfig {
+        UpgradeConfig::new(
+            Digest::hash(b"pre_state_hash").into(),
+            ProtocolVersion::V1_0_0,
+            ProtocolVersion::from_parts(2, 0, 0),
+            Some(EraId::new(1)),
+            Some(100),
+            None,
+            None,
+            Some(Ratio::new(1, 2)),
+            None,
+            BTreeMap::new(),
+            None,
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn log_fields_should_render_set_and_unset_values() {
+        let upgrade_config = make_upgrade_config();
+        let log_fields: BTreeMap<&str, String> =
+            upgrade_config.to_log_fields().into_iter().collect();
+
+        assert_eq!(log_fields.len(), 15);
+        assert_eq!(log_fields["current_protocol_version"], "1.0.0");
+        assert_eq!(log_fields["new_protocol_version"], "2.0.0");
+        assert_eq!(log_fields["activation_point"], "Some(1)");
+        assert_eq!(log_fields["new_validator_slots"], "Some(100)");
+        assert_eq!(log_fields["new_auction_delay"], "None");
+        assert_eq!(log_fields["new_locked_funds_period_millis"], "None");
+        assert_eq!(log_fields["new_round_seigniorage_rate"], "Some(1/2)");
+        assert_eq!(log_fields["new_unbonding_delay"], "None");
+        assert_eq!(log_fields["global_state_update_len"], "0");
+        assert_eq!(log_fields["global_state_update_notes_len"], "0");
+        assert_eq!(log_fields["strict_global_state_update"], "false");
+        assert_eq!(log_fields["disabled_versions_retention"], "None");
+        assert_eq!(log_fields["network_name"], "None");
+        assert_eq!(log_fields["new_config_flags"], "None");
+        assert_eq!(
+            log_fields["pre_state_hash"],
+            upgrade_config.pre_state_hash().to_string()
+        );
+    }
+
+    #[test]
+    fn should_compare_configs_ignoring_pre_state_hash() {
+        let upgrade_config = make_upgrade_config();
+        let mut other_upgrade_config = upgrade_config.clone();
+        other_upgrade_config.with_pre_state_hash(Digest::hash(b"other_pre_state_hash").into());
+
+        assert_ne!(upgrade_config, other_upgrade_config);
+        assert!(upgrade_config.equivalent_ignoring_pre_state(&other_upgrade_config));
+
+        let different_upgrade_config = UpgradeConfig {
+            new_auction_delay: Some(10),
+            ..other_upgrade_config
+        };
+        assert!(!upgrade_config.equivalent_ignoring_pre_state(&different_upgrade_config));
+    }
+
+    #[test]
+    fn should_prune_disabled_versions_beyond_retention() {
+        // every upgrade stores a different contract of the package, so each one disables a new
+        // version
+        let first_hash = ContractHash::new([20; 32]);
+        let second_hash = ContractHash::new([21; 32]);
+        let mut contract_package = make_contract_package(&[[10; 32], [11; 32]]);
+        contract_package.insert_contract_version(1, first_hash);
+        contract_package.insert_contract_version(1, second_hash);
+        let tracking_copy = make_tracking_copy(contract_package);
+        for contract_hash in &[first_hash, second_hash] {
+            tracking_copy.borrow_mut().write(
+                (*contract_hash).into(),
+                StoredValue::Contract(make_contract()),
+            );
+        }
+
+        let expected_versions = |versions: &[((u32, u32), ContractHash)]| -> ContractVersions {
+            versions
+                .iter()
+                .map(|((major, version), contract_hash)| {
+                    (ContractVersionKey::new(*major, *version), *contract_hash)
+                })
+                .collect()
+        };
+        let disabled_versions = |contract_package: &ContractPackage| -> Vec<ContractVersionKey> {
+            contract_package
+                .disabled_versions()
+                .iter()
+                .copied()
+                .collect()
+        };
+
+        upgrade_contract_at(
+            &tracking_copy,
+            CONTRACT_HASH,
+            ProtocolVersion::from_parts(2, 0, 0),
+            Some(2),
+        )
+        .expect("should upgrade");
+        let contract_package = read_contract_package(&tracking_copy);
+        assert_eq!(
+            *contract_package.versions(),
+            expected_versions(&[
+                ((1, 2), ContractHash::new([11; 32])),
+                ((1, 3), CONTRACT_HASH),
+                ((1, 4), first_hash),
+                ((1, 5), second_hash),
+                ((2, 1), CONTRACT_HASH),
+            ])
+        );
+        assert_eq!(
+            disabled_versions(&contract_package),
+            vec![ContractVersionKey::new(1, 2), ContractVersionKey::new(1, 3)]
+        );
+
+        upgrade_contract_at(
+            &tracking_copy,
+            first_hash,
+            ProtocolVersion::from_parts(3, 0, 0),
+            Some(2),
+        )
+        .expect("should upgrade");
+        let contract_package = read_contract_package(&tracking_copy);
+        assert_eq!(
+            *contract_package.versions(),
+            expected_versions(&[
+                ((1, 3), CONTRACT_HASH),
+                ((1, 4), first_hash),
+                ((1, 5), second_hash),
+                ((2, 1), CONTRACT_HASH),
+                ((3, 1), first_hash),
+            ])
+        );
+        assert_eq!(
+            disabled_versions(&contract_package),
+            vec![ContractVersionKey::new(1, 3), ContractVersionKey::new(1, 4)]
+        );
+
+        upgrade_contract_at(
+            &tracking_copy,
+            second_hash,
+            ProtocolVersion::from_parts(4, 0, 0),
+            Some(2),
+        )
+        .expect("should upgrade");
+        let contract_package = read_contract_package(&tracking_copy);
+        assert_eq!(
+            *contract_package.versions(),
+            expected_versions(&[
+                ((1, 4), first_hash),
+                ((1, 5), second_hash),
+                ((2, 1), CONTRACT_HASH),
+                ((3, 1), first_hash),
+                ((4, 1), second_hash),
+            ])
+        );
+        assert_eq!(
+            disabled_versions(&contract_package),
+            vec![ContractVersionKey::new(1, 4), ContractVersionKey::new(1, 5)]
+        );
+        assert_eq!(
+            contract_package.current_contract_version(),
+            Some(ContractVersionKey::new(4, 1))
+        );
+    }
+
+    #[test]
+    fn should_not_prune_disabled_versions_by_default() {
+        let tracking_copy = make_tracking_copy(make_contract_package(&[[10; 32], [11; 32]]));
+
+        upgrade_contract(&tracking_copy, ProtocolVersion::from_parts(2, 0, 0), None)
+            .expect("should upgrade");
+
+        let contract_package = read_contract_package(&tracking_copy);
+        assert_eq!(contract_package.disabled_versions().len(), 3);
+        assert_eq!(contract_package.versions().len(), 4);
+    }
+
+    #[test]
+    fn should_reject_empty_entry_points() {
+        let tracking_copy = make_tracking_copy(make_contract_package(&[]));
+        let system_upgrader: SystemUpgrader<InMemoryGlobalStateView> = SystemUpgrader::new(
+            ProtocolVersion::from_parts(2, 0, 0),
+            None,
+            DEFAULT_MAX_UPGRADE_READ_ATTEMPTS,
+            Rc::clone(&tracking_copy),
+            None,
+        );
+
+        let result = system_upgrader.store_contract(
+            CorrelationId::new(),
+            CONTRACT_HASH,
+            MINT,
+            EntryPoints::new(),
+        );
+        assert_matches!(result, Err(ProtocolUpgradeError::EmptyEntryPoints(name)) if name == MINT);
+
+        upgrade_contract(&tracking_copy, ProtocolVersion::from_parts(2, 0, 0), None)
+            .expect("should upgrade with non-empty entry points");
+    }
+
+    #[test]
+    fn should_retry_transient_read_failures() {
+        let tracking_copy = make_flaky_tracking_copy(2);
+        let system_upgrader: SystemUpgrader<FlakyReader> = SystemUpgrader::new(
+            ProtocolVersion::from_parts(2, 0, 0),
+            None,
+            DEFAULT_MAX_UPGRADE_READ_ATTEMPTS,
+            tracking_copy,
+            None,
+        );
+
+        system_upgrader
+            .store_contract(
+                CorrelationId::new(),
+                CONTRACT_HASH,
+                MINT,
+                mint::mint_entry_points(),
+            )
+            .expect("should upgrade after retrying");
+    }
+
+    #[test]
+    fn should_fail_when_read_retries_are_exhausted() {
+        let tracking_copy = make_flaky_tracking_copy(DEFAULT_MAX_UPGRADE_READ_ATTEMPTS);
+        let system_upgrader: SystemUpgrader<FlakyReader> = SystemUpgrader::new(
+            ProtocolVersion::from_parts(2, 0, 0),
+            None,
+            DEFAULT_MAX_UPGRADE_READ_ATTEMPTS,
+            tracking_copy,
+            None,
+        );
+
+        let result = system_upgrader.store_contract(
+            CorrelationId::new(),
+            CONTRACT_HASH,
+            MINT,
+            mint::mint_entry_points(),
+        );
+        assert_matches!(
+            result,
+            Err(ProtocolUpgradeError::UnableToRetrieveSystemContract(_))
+        );
+    }
+
+    #[test]
+    fn should_emit_a_span_per_system_contract() {
+        let tracking_copy = make_tracking_copy(make_contract_package(&[]));
+        write_registry(&tracking_copy, &[(MINT, CONTRACT_HASH)]);
+        let system_upgrader: SystemUpgrader<InMemoryGlobalStateView> = SystemUpgrader::new(
+            ProtocolVersion::from_parts(2, 0, 0),
+            None,
+            DEFAULT_MAX_UPGRADE_READ_ATTEMPTS,
+            tracking_copy,
+            None,
+        );
+        let span_names = Arc::new(Mutex::new(Vec::new()));
+        let span_recorder = SpanRecorder {
+            span_names: Arc::clone(&span_names),
+            next_id: AtomicU64::new(1),
+        };
+
+        tracing::subscriber::with_default(span_recorder, || {
+            system_upgrader
+                .upgrade_system_contracts_major_version(
+                    CorrelationId::new(),
+                    &CONTRACT_HASH,
+                    &CONTRACT_HASH,
+                    &CONTRACT_HASH,
+                    &CONTRACT_HASH,
+                )
+                .expect("should upgrade")
+        });
+
+        assert_eq!(
+            *span_names.lock().unwrap(),
+            vec![MINT, AUCTION, HANDLE_PAYMENT, STANDARD_PAYMENT]
+        );
+    }
+
+    #[test]
+    fn should_classify_upgrade_with_global_state_update_as_emergency() {
+        let mut upgrade_config = make_upgrade_config();
+        assert!(!upgrade_config.is_emergency());
+
+        upgrade_config
+            .global_state_update
+            .insert(Key::Hash([1; 32]), StoredValue::CLValue(CLValue::unit()));
+        assert!(upgrade_config.is_emergency());
+    }
+
+    #[test]
+    fn should_reject_unbonding_delay_below_auction_delay() {
+        let upgrade_config = UpgradeConfig {
+            new_auction_delay: Some(3),
+            new_unbonding_delay: Some(2),
+            ..make_upgrade_config()
+        };
+        assert_matches!(
+            upgrade_config.validate(),
+            Err(ProtocolUpgradeError::InvalidUpgradeConfig)
+        );
+    }
+
+    #[test]
+    fn should_reject_unbonding_delay_equal_to_auction_delay() {
+        let upgrade_config = UpgradeConfig {
+            new_auction_delay: Some(3),
+            new_unbonding_delay: Some(3),
+            ..make_upgrade_config()
+        };
+        assert_matches!(
+            upgrade_config.validate(),
+            Err(ProtocolUpgradeError::InvalidUpgradeConfig)
+        );
+    }
+
+    #[test]
+    fn should_accept_unbonding_delay_above_auction_delay() {
+        let upgrade_config = UpgradeConfig {
+            new_auction_delay: Some(3),
+            new_unbonding_delay: Some(4),
+            ..make_upgrade_config()
+        };
+        assert!(upgrade_config.validate().is_ok());
+        assert!(make_upgrade_config().validate().is_ok());
+    }
+
+    #[test]
+    fn validated_config_should_match_original() {
+        let upgrade_config = make_upgrade_config();
+        let validated_upgrade_config = upgrade_config.validate().unwrap();
+        assert_eq!(*validated_upgrade_config, upgrade_config);
+        assert_eq!(validated_upgrade_config.into_inner(), upgrade_config);
+    }
+
+    #[test]
+    fn should_resolve_system_contract_names() {
+        let tracking_copy = make_tracking_copy(make_contract_package(&[]));
+        let auction_hash = ContractHash::new([3; 32]);
+
+        let result = system_contract_name_for(
+            &mut tracking_copy.borrow_mut(),
+            CorrelationId::new(),
+            CONTRACT_HASH,
+        );
+        assert_matches!(
+            result,
+            Err(ProtocolUpgradeError::UnableToRetrieveSystemContractRegistry(_))
+        );
+
+        write_registry(
+            &tracking_copy,
+            &[(MINT, CONTRACT_HASH), (AUCTION, auction_hash)],
+        );
+
+        for (contract_hash, expected_name) in [
+            (CONTRACT_HASH, Some(MINT.to_string())),
+            (auction_hash, Some(AUCTION.to_string())),
+            (ContractHash::new([4; 32]), None),
+        ] {
+            let name = system_contract_name_for(
+                &mut tracking_copy.borrow_mut(),
+                CorrelationId::new(),
+                contract_hash,
+            )
+            .expect("should read registry");
+            assert_eq!(name, expected_name);
+        }
+    }
+
+    #[test]
+    fn should_chain_from_upgrade_with_matching_post_state_hash() {
+        let upgrade_config = make_upgrade_config();
+        let previous = UpgradeSuccess {
+            post_state_hash: Digest::from(upgrade_config.pre_state_hash()).into(),
+            execution_effect: ExecutionEffect::default(),
+            effect_hash: Digest::default(),
+            execution_journal: ExecutionJournal::default(),
+        };
+        assert!(upgrade_config.chains_from(&previous));
+    }
+
+    #[test]
+    fn should_not_chain_from_upgrade_with_different_post_state_hash() {
+        let upgrade_config = make_upgrade_config();
+        let previous = UpgradeSuccess {
+            post_state_hash: Digest::hash(b"other_post_state_hash").into(),
+            execution_effect: ExecutionEffect::default(),
+            effect_hash: Digest::default(),
+            execution_journal: ExecutionJournal::default(),
+        };
+        assert!(!upgrade_config.chains_from(&previous));
+    }
+
+    #[test]
+    fn should_fail_upgrade_when_registry_is_missing() {
+        let tracking_copy = make_tracking_copy(make_contract_package(&[]));
+        let system_upgrader: SystemUpgrader<InMemoryGlobalStateView> = SystemUpgrader::new(
+            ProtocolVersion::from_parts(2, 0, 0),
+            None,
+            DEFAULT_MAX_UPGRADE_READ_ATTEMPTS,
+            tracking_copy,
+            None,
+        );
+
+        let result = system_upgrader.upgrade_system_contracts_major_version(
+            CorrelationId::new(),
+            &CONTRACT_HASH,
+            &CONTRACT_HASH,
+            &CONTRACT_HASH,
+            &CONTRACT_HASH,
+        );
+        assert_matches!(
+            result,
+            Err(ProtocolUpgradeError::MissingSystemContractRegistry)
+        );
+    }
+
+    #[test]
+    fn should_reject_zero_validator_slots() {
+        let upgrade_config = UpgradeConfig {
+            new_validator_slots: Some(0),
+            ..make_upgrade_config()
+        };
+        assert_matches!(
+            upgrade_config.validate(),
+            Err(ProtocolUpgradeError::InvalidUpgradeConfig)
+        );
+    }
+
+    #[test]
+    fn should_reject_validator_slots_above_maximum() {
+        let upgrade_config = UpgradeConfig {
+            new_validator_slots: Some(MAX_NEW_VALIDATOR_SLOTS + 1),
+            ..make_upgrade_config()
+        };
+        assert_matches!(
+            upgrade_config.validate(),
+            Err(ProtocolUpgradeError::InvalidUpgradeConfig)
+        );
+    }
+
+    #[test]
+    fn should_accept_validator_slots_within_bounds() {
+        let upgrade_config = UpgradeConfig {
+            new_validator_slots: Some(MAX_NEW_VALIDATOR_SLOTS),
+            ..make_upgrade_config()
+        };
+        assert!(upgrade_config.validate().is_ok());
+    }
+
+    #[test]
+    fn state_hash_conversions_should_round_trip() {
+        let digest = Digest::hash(b"state");
+        assert_eq!(Digest::from(PreStateHash::from(digest)), digest);
+        assert_eq!(Digest::from(PostStateHash::from(digest)), digest);
+        assert_eq!(PreStateHash::from(digest).to_string(), digest.to_string());
+        assert_eq!(PostStateHash::from(digest).to_string(), digest.to_string());
+    }
+
+    #[test]
+    fn should_accept_matching_network_name() {
+        let upgrade_config = UpgradeConfig {
+            network_name: Some("casper-test".to_string()),
+            ..make_upgrade_config()
+        };
+        assert!(upgrade_config.check_network_name("casper-test").is_ok());
+    }
+
+    #[test]
+    fn should_reject_mismatching_network_name() {
+        let upgrade_config = UpgradeConfig {
+            network_name: Some("casper-test".to_string()),
+            ..make_upgrade_config()
+        };
+        assert_matches!(
+            upgrade_config.check_network_name("casper"),
+            Err(ProtocolUpgradeError::NetworkMismatch { expected, found })
+                if expected == "casper-test" && found == "casper"
+        );
+    }
+
+    #[test]
+    fn should_skip_network_name_check_when_unset() {
+        assert!(make_upgrade_config().check_network_name("casper").is_ok());
+    }
+
+    fn make_named_entry_points(name: &str) -> EntryPoints {
+        let mut entry_points = EntryPoints::new();
+        entry_points.add_entry_point(EntryPoint::default_with_name(name));
+        for required_entry_point in required_entry_points(name) {
+            entry_points.add_entry_point(EntryPoint::default_with_name(*required_entry_point));
+        }
+        entry_points
+    }
+
+    /// Provides an entry point per system contract named after the contract, along with the
+    /// contract's required entry points.
+    struct NamedEntryPointsProvider;
+
+    impl EntryPointsProvider for NamedEntryPointsProvider {
+        fn mint_entry_points(&self) -> EntryPoints {
+            make_named_entry_points(MINT)
+        }
+
+        fn auction_entry_points(&self) -> EntryPoints {
+            make_named_entry_points(AUCTION)
+        }
+
+        fn handle_payment_entry_points(&self) -> EntryPoints {
+            make_named_entry_points(HANDLE_PAYMENT)
+        }
 
+        fn standard_payment_entry_points(&self) -> EntryPoints {
+            make_named_entry_points(STANDARD_PAYMENT)
+        }
+    }
+
+    #[test]
+    fn should_install_entry_points_from_custom_provider() {
+        let tracking_copy = make_tracking_copy(make_contract_package(&[]));
+        write_registry(&tracking_copy, &[(MINT, CONTRACT_HASH)]);
+        let system_upgrader: SystemUpgrader<InMemoryGlobalStateView> = SystemUpgrader::new(
+            ProtocolVersion::from_parts(2, 0, 0),
+            None,
+            DEFAULT_MAX_UPGRADE_READ_ATTEMPTS,
+            Rc::clone(&tracking_copy),
+            Some(Box::new(NamedEntryPointsProvider)),
+        );
+
+        system_upgrader
+            .upgrade_system_contracts_major_version(
+                CorrelationId::new(),
+                &CONTRACT_HASH,
+                &CONTRACT_HASH,
+                &CONTRACT_HASH,
+                &CONTRACT_HASH,
+            )
+            .expect("should upgrade");
+
+        // All four system contracts share a hash here, so the last one written is left in place.
+        let contract = match tracking_copy
+            .borrow_mut()
+            .read(CorrelationId::new(), &CONTRACT_HASH.into())
+        {
+            Ok(Some(StoredValue::Contract(contract))) => contract,
+            other => panic!("expected contract, got {:?}", other),
+        };
+        assert_eq!(
+            contract.entry_points(),
+            &make_named_entry_points(STANDARD_PAYMENT)
+        );
+    }
+
+    #[test]
+    fn should_return_all_validation_errors() {
+        let upgrade_config = UpgradeConfig {
+            new_validator_slots: Some(0),
+            new_round_seigniorage_rate: Some(Ratio::new(3, 2)),
+            new_auction_delay: Some(3),
+            new_unbonding_delay: Some(3),
+            ..make_upgrade_config()
+        };
+
+        let errors = upgrade_config
+            .validate_all()
+            .expect_err("should fail validation");
+        assert_eq!(errors.len(), 3);
+        assert!(errors
+            .iter()
+            .all(|error| matches!(error, ProtocolUpgradeError::InvalidUpgradeConfig)));
+        assert_matches!(
+            upgrade_config.validate(),
+            Err(ProtocolUpgradeError::InvalidUpgradeConfig)
+        );
+        assert!(make_upgrade_config().validate_all().is_ok());
+    }
+
+    #[test]
+    fn should_check_upgrade_path_against_supported_paths() {
+        let v1_0_0 = ProtocolVersion::V1_0_0;
+        let v1_1_0 = ProtocolVersion::from_parts(1, 1, 0);
+        let v2_0_0 = ProtocolVersion::from_parts(2, 0, 0);
+        let supported_upgrade_paths = [(v1_0_0, v1_1_0), (v1_1_0, v2_0_0)];
+
+        let adjacent_jump = UpgradeConfig {
+            current_protocol_version: v1_0_0,
+            new_protocol_version: v1_1_0,
+            ..make_upgrade_config()
+        };
+        assert!(adjacent_jump.is_supported_path_in(&supported_upgrade_paths));
+
+        let skip = UpgradeConfig {
+            current_protocol_version: v1_0_0,
+            new_protocol_version: v2_0_0,
+            ..make_upgrade_config()
+        };
+        assert!(!skip.is_supported_path_in(&supported_upgrade_paths));
+
+        let unlisted = UpgradeConfig {
+            current_protocol_version: v2_0_0,
+            new_protocol_version: ProtocolVersion::from_parts(3, 0, 0),
+            ..make_upgrade_config()
+        };
+        assert!(unlisted.is_supported_path_in(&supported_upgrade_paths));
+    }
+
+    #[test]
+    fn should_serialize_errors_with_stable_tags() {
+        let errors = vec![
+            (
+                ProtocolUpgradeError::InvalidUpgradeConfig,
+                "invalid_upgrade_config",
+            ),
+            (
+                ProtocolUpgradeError::MissingSystemContractRegistry,
+                "missing_system_contract_registry",
+            ),
+            (
+                ProtocolUpgradeError::UnableToRetrieveSystemContractRegistry("registry".into()),
+                "unable_to_retrieve_system_contract_registry",
+            ),
+            (
+                ProtocolUpgradeError::UnableToRetrieveSystemContract(MINT.into()),
+                "unable_to_retrieve_system_contract",
+            ),
+            (
+                ProtocolUpgradeError::UnableToRetrieveSystemContractPackage(MINT.into()),
+                "unable_to_retrieve_system_contract_package",
+            ),
+            (
+                ProtocolUpgradeError::FailedToDisablePreviousVersion(MINT.into()),
+                "failed_to_disable_previous_version",
+            ),
+            (
+                ProtocolUpgradeError::Bytesrepr(bytesrepr::Error::EarlyEndOfStream),
+                "bytesrepr",
+            ),
+            (
+                ProtocolUpgradeError::FailedToCreateSystemRegistry,
+                "failed_to_create_system_registry",
+            ),
+            (
+                ProtocolUpgradeError::FailedMigration("missing value".into()),
+                "failed_migration",
+            ),
+            (
+                ProtocolUpgradeError::EmptyEntryPoints(MINT.into()),
+                "empty_entry_points",
+            ),
+            (
+                ProtocolUpgradeError::NetworkMismatch {
+                    expected: "casper".into(),
+                    found: "casper-test".into(),
+                },
+                "network_mismatch",
+            ),
+            (
+                ProtocolUpgradeError::UnknownConfigFlag("flag".into()),
+                "unknown_config_flag",
+            ),
+            (
+                ProtocolUpgradeError::NoEnabledVersions(MINT.into()),
+                "no_enabled_versions",
+            ),
+            (
+                ProtocolUpgradeError::UnsupportedUpgradePath {
+                    from: ProtocolVersion::V1_0_0,
+                    to: ProtocolVersion::from_parts(3, 0, 0),
+                },
+                "unsupported_upgrade_path",
+            ),
+            (
+                ProtocolUpgradeError::PackageHashMismatch {
+                    contract: MINT.into(),
+                    contract_hash: CONTRACT_HASH,
+                    package_hash: CONTRACT_PACKAGE_HASH,
+                },
+                "package_hash_mismatch",
+            ),
+            (
+                ProtocolUpgradeError::MissingRequiredEntryPoint {
+                    contract: AUCTION.into(),
+                    entry_point: auction::METHOD_RUN_AUCTION.into(),
+                },
+                "missing_required_entry_point",
+            ),
+            (
+                ProtocolUpgradeError::UpdateTargetsSystemContract(CONTRACT_HASH.into()),
+                "update_targets_system_contract",
+            ),
+            (
+                ProtocolUpgradeError::StoredVersionNewerThanTarget {
+                    stored: ProtocolVersion::from_parts(3, 0, 0),
+                    target: ProtocolVersion::from_parts(2, 0, 0),
+                },
+                "stored_version_newer_than_target",
+            ),
+        ];
+
+        for (error, expected_kind) in errors {
+            let json = serde_json::to_value(&error).unwrap();
+            assert_eq!(json["kind"], expected_kind);
+        }
+    }
+
+    #[test]
+    fn should_serialize_bytesrepr_error_as_string() {
+        let error = ProtocolUpgradeError::Bytesrepr(bytesrepr::Error::EarlyEndOfStream);
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(
+            json["details"],
+            bytesrepr::Error::EarlyEndOfStream.to_string()
+        );
+    }
+
+    #[test]
+    fn should_reject_contract_package_without_enabled_versions() {
+        let mut contract_package = make_contract_package(&[]);
+        assert!(check_enabled_versions(&contract_package, MINT).is_ok());
+
+        // As if the new version's insert had been skipped after disabling the current one.
         contract_package
-            .insert_contract_version(self.new_protocol_version.value().major, contract_hash);
+            .disable_contract_version(CONTRACT_HASH)
+            .expect("should disable");
+        assert_matches!(
+            check_enabled_versions(&contract_package, MINT),
+            Err(ProtocolUpgradeError::NoEnabledVersions(name)) if name == MINT
+        );
+    }
 
-        self.tracking_copy.borrow_mut().write(
-            contract_package_key,
-            StoredValue::ContractPackage(contract_package),
+    #[test]
+    fn should_reject_package_not_listing_contract() {
+        // The package under the contract's recorded package hash belongs to another contract.
+        let mut contract_package = ContractPackage::default();
+        contract_package.insert_contract_version(1, ContractHash::new([2; 32]));
+        let tracking_copy = make_tracking_copy(contract_package);
+
+        assert_matches!(
+            upgrade_contract(&tracking_copy, ProtocolVersion::from_parts(2, 0, 0), None),
+            Err(ProtocolUpgradeError::PackageHashMismatch {
+                contract,
+                contract_hash,
+                package_hash,
+            }) if contract == MINT
+                && contract_hash == CONTRACT_HASH
+                && package_hash == CONTRACT_PACKAGE_HASH
         );
+    }
 
-        Ok(())
+    #[test]
+    fn should_reject_stored_contract_newer_than_target() {
+        let tracking_copy = make_tracking_copy(make_contract_package(&[]));
+        let stored_version = ProtocolVersion::from_parts(3, 0, 0);
+        let newer_contract = Contract::new(
+            CONTRACT_PACKAGE_HASH,
+            ContractWasmHash::new([0; 32]),
+            NamedKeys::new(),
+            EntryPoints::new(),
+            stored_version,
+        );
+        tracking_copy
+            .borrow_mut()
+            .write(CONTRACT_HASH.into(), StoredValue::Contract(newer_contract));
+
+        let target_version = ProtocolVersion::from_parts(2, 0, 0);
+        assert_matches!(
+            upgrade_contract(&tracking_copy, target_version, None),
+            Err(ProtocolUpgradeError::StoredVersionNewerThanTarget { stored, target })
+                if stored == stored_version && target == target_version
+        );
+    }
+
+    #[test]
+    fn should_reject_entry_points_missing_a_required_one() {
+        let mut entry_points = EntryPoints::new();
+        for entry_point in auction::auction_entry_points().take_entry_points() {
+            if entry_point.name() != auction::METHOD_RUN_AUCTION {
+                entry_points.add_entry_point(entry_point);
+            }
+        }
+
+        assert_matches!(
+            check_required_entry_points(&entry_points, AUCTION),
+            Err(ProtocolUpgradeError::MissingRequiredEntryPoint { contract, entry_point })
+                if contract == AUCTION && entry_point == auction::METHOD_RUN_AUCTION
+        );
+        assert!(check_required_entry_points(&auction::auction_entry_points(), AUCTION).is_ok());
+    }
+
+    #[test]
+    fn validation_report_should_fail_on_errors_only() {
+        let mut report = UpgradeValidationReport::default();
+        report.push_warning("first warning".to_string());
+        assert!(report.is_ok());
+        assert!(report.clone().into_result().is_ok());
+
+        report.push_error(ProtocolUpgradeError::InvalidUpgradeConfig);
+        report.push_warning("second warning".to_string());
+        report.push_error(ProtocolUpgradeError::UnknownConfigFlag("flag".to_string()));
+        assert!(!report.is_ok());
+        assert_eq!(report.warnings().len(), 2);
+        assert_eq!(report.errors().len(), 2);
+        let errors = report.into_result().expect_err("should fail");
+        assert_matches!(
+            errors.as_slice(),
+            [
+                ProtocolUpgradeError::InvalidUpgradeConfig,
+                ProtocolUpgradeError::UnknownConfigFlag(flag_name)
+            ] if flag_name == "flag"
+        );
+    }
+
+    #[test]
+    fn should_warn_about_notes_on_keys_outside_global_state_update() {
+        let mut upgrade_config = make_upgrade_config();
+        let mut global_state_update_notes = BTreeMap::new();
+        global_state_update_notes.insert(Key::Hash([7; 32]), "stray note".to_string());
+        upgrade_config.with_global_state_update_notes(global_state_update_notes);
+
+        let report = upgrade_config.validation_report();
+        assert!(report.is_ok());
+        assert_eq!(report.warnings().len(), 1);
+    }
+
+    #[test]
+    fn should_accept_known_config_flags_only() {
+        let mut upgrade_config = make_upgrade_config();
+        let mut new_config_flags = BTreeMap::new();
+        new_config_flags.insert(
+            GENESIS_TIMESTAMP_SEMANTICS_FLAG.to_string(),
+            CLValue::from_t(true).unwrap(),
+        );
+        upgrade_config.with_new_config_flags(new_config_flags.clone());
+        assert!(upgrade_config.validate().is_ok());
+
+        new_config_flags.insert("unknown_flag".to_string(), CLValue::from_t(1u8).unwrap());
+        upgrade_config.with_new_config_flags(new_config_flags);
+        assert_matches!(
+            upgrade_config.validate(),
+            Err(ProtocolUpgradeError::UnknownConfigFlag(flag_name)) if flag_name == "unknown_flag"
+        );
+    }
+
+    #[test]
+    fn fingerprint_should_ignore_pre_state_hash_only() {
+        let upgrade_config = make_upgrade_config();
+        let fingerprint = upgrade_config.fingerprint().unwrap();
+
+        let mut other_node_upgrade_config = upgrade_config.clone();
+        other_node_upgrade_config.with_pre_state_hash(Digest::hash(b"other_pre_state_hash").into());
+        assert_eq!(
+            other_node_upgrade_config.fingerprint().unwrap(),
+            fingerprint
+        );
+
+        let different_upgrade_config = UpgradeConfig {
+            new_auction_delay: Some(3),
+            ..upgrade_config
+        };
+        assert_ne!(different_upgrade_config.fingerprint().unwrap(), fingerprint);
+    }
+
+    #[test]
+    fn should_detect_no_op_upgrade() {
+        let patch_bump = UpgradeConfig {
+            new_protocol_version: ProtocolVersion::from_parts(1, 0, 1),
+            new_validator_slots: None,
+            new_round_seigniorage_rate: None,
+            ..make_upgrade_config()
+        };
+        assert!(patch_bump.is_no_op());
+
+        let major_bump = UpgradeConfig {
+            new_protocol_version: ProtocolVersion::from_parts(2, 0, 0),
+            ..patch_bump.clone()
+        };
+        assert!(!major_bump.is_no_op());
+
+        let new_auction_delay = UpgradeConfig {
+            new_auction_delay: Some(3),
+            ..patch_bump.clone()
+        };
+        assert!(!new_auction_delay.is_no_op());
+
+        let mut global_state_update = BTreeMap::new();
+        global_state_update.insert(Key::Hash([1; 32]), StoredValue::CLValue(CLValue::unit()));
+        let emergency = UpgradeConfig {
+            global_state_update,
+            ..patch_bump
+        };
+        assert!(!emergency.is_no_op());
     }
 }
@@ -0,0 +1,577 @@
+//! Support for applying upgrades against in-memory global state.
+//!
+//! Available with the `test-support` feature, so upgrade tests can apply an [`UpgradeConfig`] and
+//! inspect its effects without running genesis or touching disk.
+use casper_hashing::Digest;
+use casper_types::{Key, ProtocolVersion, StoredValue};
+
+use crate::{
+    core::{
+        engine_state::{EngineConfig, EngineState, Error, UpgradeConfig, UpgradeSuccess},
+        execution,
+    },
+    shared::newtypes::CorrelationId,
+    storage::global_state::in_memory::InMemoryGlobalState,
+};
+
+/// Applies upgrades on top of in-memory global state seeded with arbitrary values.
+#[derive(Debug)]
+pub struct InMemoryUpgradeHarness {
+    engine_state: EngineState<InMemoryGlobalState>,
+    state_root_hash: Digest,
+}
+
+impl InMemoryUpgradeHarness {
+    /// Creates a harness whose global state holds the given values.
+    ///
+    /// The values should include the system contract registry and the system contracts and
+    /// contract packages it refers to.
+    pub fn new(pairs: &[(Key, StoredValue)]) -> Result<Self, Error> {
+        let (global_state, state_root_hash) =
+            InMemoryGlobalState::from_pairs(CorrelationId::new(), pairs)?;
+        Ok(InMemoryUpgradeHarness {
+            engine_state: EngineState::new(global_state, EngineConfig::default()),
+            state_root_hash,
+        })
+    }
+
+    /// Binds the engine state to the network named `network_name`, so that upgrades intended for
+    /// another network are rejected.
+    pub fn with_network_name(mut self, network_name: &str) -> Self {
+        self.engine_state = self.engine_state.with_network_name(network_name);
+        self
+    }
+
+    /// Replaces the upgrade paths which upgrades must follow.
+    pub fn with_supported_upgrade_paths(
+        mut self,
+        supported_upgrade_paths: Vec<(ProtocolVersion, ProtocolVersion)>,
+    ) -> Self {
+        self.engine_state = self
+            .engine_state
+            .with_supported_upgrade_paths(supported_upgrade_paths);
+        self
+    }
+
+    /// Returns the engine state the upgrades are applied with.
+    pub fn engine_state(&self) -> &EngineState<InMemoryGlobalState> {
+        &self.engine_state
+    }
+
+    /// Returns the current state root hash.
+    pub fn state_root_hash(&self) -> Digest {
+        self.state_root_hash
+    }
+
+    /// Validates and applies the given upgrade on top of the current state root hash.
+    ///
+    /// The upgrade config's pre state hash is replaced by the current state root hash, which is
+    /// advanced to the upgrade's post state hash on success.
+    pub fn upgrade(&mut self, mut upgrade_config: UpgradeConfig) -> Result<UpgradeSuccess, Error> {
+        upgrade_config.with_pre_state_hash(self.state_root_hash.into());
+        let upgrade_config = upgrade_config.validate().map_err(Error::ProtocolUpgrade)?;
+        let upgrade_success = self
+            .engine_state
+            .commit_upgrade(CorrelationId::new(), upgrade_config)?;
+        self.state_root_hash = upgrade_success.post_state_hash.into();
+        Ok(upgrade_success)
+    }
+
+    /// Reads the value under `key` at the current state root hash.
+    pub fn read(&self, key: &Key) -> Result<Option<StoredValue>, Error> {
+        let mut tracking_copy = self
+            .engine_state
+            .tracking_copy(self.state_root_hash)?
+            .ok_or(Error::RootNotFound(self.state_root_hash))?;
+        let value = tracking_copy
+            .read(CorrelationId::new(), key)
+            .map_err(Into::<execution::Error>::into)?;
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::BTreeMap,
+        fmt::Debug,
+        sync::{Arc, Mutex},
+    };
+
+    use tracing::{
+        field::{Field, Visit},
+        span::{Attributes, Id, Record},
+        Event, Metadata, Subscriber,
+    };
+
+    use casper_types::{
+        contracts::NamedKeys,
+        system::{auction::VALIDATOR_SLOTS_KEY, AUCTION, HANDLE_PAYMENT, MINT, STANDARD_PAYMENT},
+        CLValue, Contract, ContractHash, ContractPackage, ContractPackageHash, ContractWasmHash,
+        EntryPoints, EraId, ProtocolVersion,
+    };
+
+    use super::*;
+    use crate::{core::engine_state::upgrade::ProtocolUpgradeError, shared::transform::Transform};
+
+    const SYSTEM_CONTRACTS: [&str; 4] = [MINT, AUCTION, HANDLE_PAYMENT, STANDARD_PAYMENT];
+
+    type EventFields = BTreeMap<&'static str, String>;
+
+    /// Records the fields of every event.
+    struct EventRecorder {
+        events: Arc<Mutex<Vec<EventFields>>>,
+    }
+
+    struct FieldVisitor<'a>(&'a mut EventFields);
+
+    impl Visit for FieldVisitor<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0.insert(field.name(), format!("{:?}", value));
+        }
+    }
+
+    impl Subscriber for EventRecorder {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _attributes: &Attributes<'_>) -> Id {
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = EventFields::new();
+            event.record(&mut FieldVisitor(&mut fields));
+            self.events.lock().unwrap().push(fields);
+        }
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+    }
+
+    fn contract_hash(index: u8) -> ContractHash {
+        ContractHash::new([index + 1; 32])
+    }
+
+    fn system_contract_pairs() -> Vec<(Key, StoredValue)> {
+        let mut pairs = Vec::new();
+        let mut registry = BTreeMap::new();
+        for (index, name) in SYSTEM_CONTRACTS.iter().enumerate() {
+            let contract_hash = contract_hash(index as u8);
+            let contract_package_hash = ContractPackageHash::new([index as u8 + 101; 32]);
+            let contract = Contract::new(
+                contract_package_hash,
+                ContractWasmHash::new([0; 32]),
+                NamedKeys::new(),
+                EntryPoints::new(),
+                ProtocolVersion::V1_0_0,
+            );
+            let mut contract_package = ContractPackage::default();
+            contract_package.insert_contract_version(1, contract_hash);
+
+            pairs.push((contract_hash.into(), StoredValue::Contract(contract)));
+            pairs.push((
+                contract_package_hash.into(),
+                StoredValue::ContractPackage(contract_package),
+            ));
+            registry.insert(name.to_string(), contract_hash);
+        }
+        pairs.push((
+            Key::SystemContractRegistry,
+            StoredValue::CLValue(CLValue::from_t(registry).unwrap()),
+        ));
+        pairs
+    }
+
+    fn make_major_upgrade_config(
+        pre_state_hash: Digest,
+        global_state_update: BTreeMap<Key, StoredValue>,
+    ) -> UpgradeConfig {
+        UpgradeConfig::new(
+            pre_state_hash.into(),
+            ProtocolVersion::V1_0_0,
+            ProtocolVersion::from_parts(2, 0, 0),
+            Some(EraId::new(1)),
+            None,
+            None,
+            None,
+            None,
+            None,
+            global_state_update,
+            None,
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn should_apply_major_upgrade_in_memory() {
+        let mut harness =
+            InMemoryUpgradeHarness::new(&system_contract_pairs()).expect("should create harness");
+        let pre_state_hash = harness.state_root_hash();
+
+        let upgrade_config = make_major_upgrade_config(pre_state_hash, BTreeMap::new());
+        let new_protocol_version = upgrade_config.new_protocol_version();
+        let upgrade_success = harness.upgrade(upgrade_config).expect("should upgrade");
+
+        let post_state_hash = Digest::from(upgrade_success.post_state_hash);
+        assert_ne!(post_state_hash, pre_state_hash);
+        assert_eq!(harness.state_root_hash(), post_state_hash);
+        for index in 0..SYSTEM_CONTRACTS.len() {
+            let contract_key = Key::from(contract_hash(index as u8));
+            let contract = match harness.read(&contract_key).expect("should read") {
+                Some(StoredValue::Contract(contract)) => contract,
+                other => panic!("expected contract, got {:?}", other),
+            };
+            assert_eq!(contract.protocol_version(), new_protocol_version);
+
+            let contract_package_key = Key::from(contract.contract_package_hash());
+            let contract_package = match harness.read(&contract_package_key).expect("should read") {
+                Some(StoredValue::ContractPackage(contract_package)) => contract_package,
+                other => panic!("expected contract package, got {:?}", other),
+            };
+            assert_eq!(contract_package.enabled_versions().len(), 1);
+            assert_eq!(
+                contract_package.current_contract_hash(),
+                Some(contract_hash(index as u8))
+            );
+        }
+    }
+
+    #[test]
+    fn should_log_upgrade_summary_once() {
+        let mut harness =
+            InMemoryUpgradeHarness::new(&system_contract_pairs()).expect("should create harness");
+        let upgrade_config = make_major_upgrade_config(harness.state_root_hash(), BTreeMap::new());
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let event_recorder = EventRecorder {
+            events: Arc::clone(&events),
+        };
+
+        let upgrade_success = tracing::subscriber::with_default(event_recorder, || {
+            harness.upgrade(upgrade_config).expect("should upgrade")
+        });
+
+        let events = events.lock().unwrap();
+        let summaries: Vec<&EventFields> = events
+            .iter()
+            .filter(|fields| fields.get("message").map(String::as_str) == Some("upgrade complete"))
+            .collect();
+        assert_eq!(summaries.len(), 1);
+        let summary = summaries[0];
+        assert_eq!(summary["from_version"], "1.0.0");
+        assert_eq!(summary["to_version"], "2.0.0");
+        assert_eq!(
+            summary["contracts_upgraded"],
+            SYSTEM_CONTRACTS.len().to_string()
+        );
+        assert_eq!(
+            summary["entries_written"],
+            upgrade_success
+                .execution_effect
+                .transforms
+                .len()
+                .to_string()
+        );
+        assert_eq!(
+            summary["post_state_hash"],
+            upgrade_success.post_state_hash.to_string()
+        );
+        assert!(summary.contains_key("duration"));
+    }
+
+    #[test]
+    fn should_log_global_state_update_notes() {
+        let mut harness =
+            InMemoryUpgradeHarness::new(&system_contract_pairs()).expect("should create harness");
+        let annotated_key = Key::Hash([200; 32]);
+        let unannotated_key = Key::Hash([201; 32]);
+        let mut global_state_update = BTreeMap::new();
+        for key in &[annotated_key, unannotated_key] {
+            global_state_update.insert(*key, StoredValue::CLValue(CLValue::from_t(1u8).unwrap()));
+        }
+        let mut global_state_update_notes = BTreeMap::new();
+        global_state_update_notes.insert(annotated_key, "restore lost balance".to_string());
+
+        let mut upgrade_config =
+            make_major_upgrade_config(harness.state_root_hash(), global_state_update);
+        upgrade_config.with_global_state_update_notes(global_state_update_notes.clone());
+        assert_eq!(
+            *upgrade_config.global_state_update_notes(),
+            global_state_update_notes
+        );
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let event_recorder = EventRecorder {
+            events: Arc::clone(&events),
+        };
+
+        tracing::subscriber::with_default(event_recorder, || {
+            harness.upgrade(upgrade_config).expect("should upgrade")
+        });
+
+        let events = events.lock().unwrap();
+        let notes: Vec<(&str, &str)> = events
+            .iter()
+            .filter(|fields| {
+                fields.get("message").map(String::as_str)
+                    == Some("applying annotated global state update entry")
+            })
+            .map(|fields| (fields["key"].as_str(), fields["note"].as_str()))
+            .collect();
+        let annotated_key = annotated_key.to_string();
+        assert_eq!(
+            notes,
+            vec![(annotated_key.as_str(), "restore lost balance")]
+        );
+    }
+
+    #[test]
+    fn should_let_upgrade_observers_register_observers() {
+        let (global_state, state_root_hash) =
+            InMemoryGlobalState::from_pairs(CorrelationId::new(), &system_contract_pairs())
+                .expect("should create global state");
+        let engine_state = Arc::new(EngineState::new(global_state, EngineConfig::default()));
+        let notifications = Arc::new(Mutex::new(0));
+
+        let weak_engine_state = Arc::downgrade(&engine_state);
+        let observed = Arc::clone(&notifications);
+        engine_state.register_upgrade_observer(Arc::new(move |_upgrade_success| {
+            *observed.lock().unwrap() += 1;
+            if let Some(engine_state) = weak_engine_state.upgrade() {
+                engine_state.register_upgrade_observer(Arc::new(|_upgrade_success| {}));
+            }
+        }));
+
+        let upgrade_config = make_major_upgrade_config(state_root_hash, BTreeMap::new())
+            .validate()
+            .expect("should validate");
+        engine_state
+            .commit_upgrade(CorrelationId::new(), upgrade_config)
+            .expect("should upgrade");
+        assert_eq!(*notifications.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn should_reject_unsupported_upgrade_path() {
+        let v1_1_0 = ProtocolVersion::from_parts(1, 1, 0);
+        let v2_0_0 = ProtocolVersion::from_parts(2, 0, 0);
+        let mut harness = InMemoryUpgradeHarness::new(&system_contract_pairs())
+            .expect("should create harness")
+            .with_supported_upgrade_paths(vec![
+                (ProtocolVersion::V1_0_0, v1_1_0),
+                (v1_1_0, v2_0_0),
+            ]);
+        let pre_state_hash = harness.state_root_hash();
+
+        // Skipping 1.1.0 is refused, even though the config itself is valid.
+        let upgrade_config = make_major_upgrade_config(pre_state_hash, BTreeMap::new());
+        assert!(upgrade_config.validate().is_ok());
+        let error = harness.upgrade(upgrade_config).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::ProtocolUpgrade(ProtocolUpgradeError::UnsupportedUpgradePath { from, to })
+                if from == ProtocolVersion::V1_0_0 && to == v2_0_0
+        ));
+        assert_eq!(harness.state_root_hash(), pre_state_hash);
+
+        let mut global_state_update = BTreeMap::new();
+        global_state_update.insert(
+            Key::Hash([200; 32]),
+            StoredValue::CLValue(CLValue::from_t(1u8).unwrap()),
+        );
+        let upgrade_config = UpgradeConfig::new(
+            pre_state_hash.into(),
+            ProtocolVersion::V1_0_0,
+            v1_1_0,
+            Some(EraId::new(1)),
+            None,
+            None,
+            None,
+            None,
+            None,
+            global_state_update,
+            None,
+            None,
+            None,
+        );
+        harness.upgrade(upgrade_config).expect("should upgrade");
+        assert_ne!(harness.state_root_hash(), pre_state_hash);
+    }
+
+    #[test]
+    fn should_only_fail_on_unreadable_system_contract_in_strict_mode() {
+        // The registry lists a contract which isn't in global state.
+        let missing_contract_hash = ContractHash::new([250; 32]);
+        let mut pairs = system_contract_pairs();
+        let (registry_key, _) = pairs.pop().expect("should have registry");
+        let mut registry: BTreeMap<String, ContractHash> = SYSTEM_CONTRACTS
+            .iter()
+            .enumerate()
+            .map(|(index, name)| (name.to_string(), contract_hash(index as u8)))
+            .collect();
+        registry.insert("missing".to_string(), missing_contract_hash);
+        pairs.push((
+            registry_key,
+            StoredValue::CLValue(CLValue::from_t(registry).unwrap()),
+        ));
+        let mut harness = InMemoryUpgradeHarness::new(&pairs).expect("should create harness");
+        let pre_state_hash = harness.state_root_hash();
+
+        let make_upgrade_config = |strict: bool| {
+            let mut global_state_update = BTreeMap::new();
+            global_state_update.insert(
+                Key::Hash([200; 32]),
+                StoredValue::CLValue(CLValue::from_t(1u8).unwrap()),
+            );
+            let mut upgrade_config = UpgradeConfig::new(
+                pre_state_hash.into(),
+                ProtocolVersion::V1_0_0,
+                ProtocolVersion::from_parts(1, 1, 0),
+                Some(EraId::new(1)),
+                None,
+                None,
+                None,
+                None,
+                None,
+                global_state_update,
+                None,
+                None,
+                None,
+            );
+            upgrade_config.with_strict_global_state_update(strict);
+            upgrade_config
+        };
+        let error = harness.upgrade(make_upgrade_config(true)).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::Exec(execution::Error::KeyNotFound(key))
+                if key == Key::from(missing_contract_hash)
+        ));
+
+        assert_eq!(harness.state_root_hash(), pre_state_hash);
+
+        harness
+            .upgrade(make_upgrade_config(false))
+            .expect("should upgrade");
+        assert_ne!(harness.state_root_hash(), pre_state_hash);
+    }
+
+    #[test]
+    fn should_reject_upgrade_for_another_network() {
+        let mut harness = InMemoryUpgradeHarness::new(&system_contract_pairs())
+            .expect("should create harness")
+            .with_network_name("casper");
+        let pre_state_hash = harness.state_root_hash();
+        let make_upgrade_config = |network_name: &str| {
+            UpgradeConfig::new(
+                pre_state_hash.into(),
+                ProtocolVersion::V1_0_0,
+                ProtocolVersion::from_parts(2, 0, 0),
+                Some(EraId::new(1)),
+                None,
+                None,
+                None,
+                None,
+                None,
+                BTreeMap::new(),
+                None,
+                Some(network_name.to_string()),
+                None,
+            )
+        };
+
+        let error = harness
+            .upgrade(make_upgrade_config("casper-test"))
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            Error::ProtocolUpgrade(ProtocolUpgradeError::NetworkMismatch { expected, found })
+                if expected == "casper-test" && found == "casper"
+        ));
+        assert_eq!(harness.state_root_hash(), pre_state_hash);
+
+        harness
+            .upgrade(make_upgrade_config("casper"))
+            .expect("should upgrade");
+        assert_ne!(harness.state_root_hash(), pre_state_hash);
+    }
+
+    #[test]
+    fn should_fail_upgrade_without_system_contract_registry() {
+        let pairs: Vec<(Key, StoredValue)> = system_contract_pairs()
+            .into_iter()
+            .filter(|(key, _)| *key != Key::SystemContractRegistry)
+            .collect();
+        let mut harness = InMemoryUpgradeHarness::new(&pairs).expect("should create harness");
+        let pre_state_hash = harness.state_root_hash();
+        let upgrade_config = make_major_upgrade_config(pre_state_hash, BTreeMap::new());
+
+        let error = harness.upgrade(upgrade_config).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::ProtocolUpgrade(ProtocolUpgradeError::MissingSystemContractRegistry)
+        ));
+        assert_eq!(harness.state_root_hash(), pre_state_hash);
+    }
+
+    #[test]
+    fn should_write_registry_from_global_state_update_once() {
+        let mut pairs = system_contract_pairs();
+        let (registry_key, registry) = pairs.pop().expect("should have registry");
+        assert_eq!(registry_key, Key::SystemContractRegistry);
+        let mut harness = InMemoryUpgradeHarness::new(&pairs).expect("should create harness");
+        let mut global_state_update = BTreeMap::new();
+        global_state_update.insert(registry_key, registry.clone());
+        let upgrade_config =
+            make_major_upgrade_config(harness.state_root_hash(), global_state_update);
+
+        let upgrade_success = harness.upgrade(upgrade_config).expect("should upgrade");
+
+        let registry_writes = upgrade_success
+            .execution_journal
+            .iter()
+            .filter(|(key, transform)| {
+                *key == registry_key && matches!(transform, Transform::Write(_))
+            })
+            .count();
+        assert_eq!(registry_writes, 1);
+        assert_eq!(harness.read(&registry_key).unwrap(), Some(registry));
+    }
+
+    #[test]
+    fn should_fail_to_diff_config_on_missing_named_key() {
+        let harness =
+            InMemoryUpgradeHarness::new(&system_contract_pairs()).expect("should create harness");
+        let upgrade_config = UpgradeConfig::new(
+            harness.state_root_hash().into(),
+            ProtocolVersion::V1_0_0,
+            ProtocolVersion::from_parts(2, 0, 0),
+            Some(EraId::new(1)),
+            Some(7),
+            None,
+            None,
+            None,
+            None,
+            BTreeMap::new(),
+            None,
+            None,
+            None,
+        );
+
+        // The system contracts set up here have no named keys at all.
+        let error = harness
+            .engine_state()
+            .get_upgrade_config_changes(CorrelationId::new(), &upgrade_config)
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            Error::Exec(execution::Error::NamedKeyNotFound(name)) if name == VALIDATOR_SLOTS_KEY
+        ));
+    }
+}
@@ -1,4 +1,4 @@
-use std::{cell::RefCell, collections::BTreeSet, rc::Rc};
+use std::{cell::RefCell, collections::BTreeSet, rc::Rc, sync::Arc};
 
 use parity_wasm::elements::Module;
 use tracing::warn;
@@ -21,7 +21,10 @@ use crate::{
         runtime_context::{self, RuntimeContext},
         tracking_copy::{TrackingCopy, TrackingCopyExt},
     },
-    shared::{execution_journal::ExecutionJournal, newtypes::CorrelationId},
+    shared::{
+        execution_journal::ExecutionJournal, newtypes::CorrelationId,
+        wasm_module_cache::WasmModuleCache,
+    },
     storage::global_state::StateReader,
 };
 
@@ -71,13 +74,17 @@ macro_rules! on_fail_charge {
 /// Executor object deals with execution of WASM modules.
 pub struct Executor {
     config: EngineConfig,
+    wasm_module_cache: Arc<WasmModuleCache>,
 }
 
 #[allow(clippy::too_many_arguments)]
 impl Executor {
     /// Creates new executor object.
-    pub fn new(config: EngineConfig) -> Self {
-        Executor { config }
+    pub fn new(config: EngineConfig, wasm_module_cache: Arc<WasmModuleCache>) -> Self {
+        Executor {
+            config,
+            wasm_module_cache,
+        }
     }
 
     /// Returns config.
@@ -160,7 +167,14 @@ impl Executor {
             transfers,
         );
 
-        let mut runtime = Runtime::new(self.config, memory, module, context, stack);
+        let mut runtime = Runtime::new(
+            self.config,
+            Arc::clone(&self.wasm_module_cache),
+            memory,
+            module,
+            context,
+            stack,
+        );
 
         let accounts_access_rights = {
             let keys: Vec<Key> = account.named_keys().values().cloned().collect();
@@ -632,7 +646,14 @@ impl Executor {
         let (instance, memory) =
             instance_and_memory(module.clone(), protocol_version, self.config.wasm_config())?;
 
-        let runtime = Runtime::new(self.config, memory, module, runtime_context, stack);
+        let runtime = Runtime::new(
+            self.config,
+            Arc::clone(&self.wasm_module_cache),
+            memory,
+            module,
+            runtime_context,
+            stack,
+        );
 
         Ok((instance, runtime))
     }
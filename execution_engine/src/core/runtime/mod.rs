@@ -14,6 +14,7 @@ use std::{
     collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     convert::TryFrom,
     iter::IntoIterator,
+    sync::Arc,
 };
 
 use itertools::Itertools;
@@ -54,6 +55,7 @@ use crate::{
     shared::{
         host_function_costs::{Cost, HostFunction},
         wasm_config::WasmConfig,
+        wasm_module_cache::WasmModuleCache,
     },
     storage::global_state::StateReader,
 };
@@ -62,6 +64,7 @@ pub use stack::{RuntimeStack, RuntimeStackFrame, RuntimeStackOverflow};
 /// Represents the runtime properties of a WASM execution.
 pub struct Runtime<'a, R> {
     config: EngineConfig,
+    wasm_module_cache: Arc<WasmModuleCache>,
     memory: MemoryRef,
     module: Module,
     host_buffer: Option<CLValue>,
@@ -984,6 +987,7 @@ where
     /// Creates a new runtime instance.
     pub(crate) fn new(
         config: EngineConfig,
+        wasm_module_cache: Arc<WasmModuleCache>,
         memory: MemoryRef,
         module: Module,
         context: RuntimeContext<'a, R>,
@@ -992,6 +996,7 @@ where
         Self::check_preconditions(&stack);
         Runtime {
             config,
+            wasm_module_cache,
             memory,
             module,
             host_buffer: None,
@@ -1007,6 +1012,7 @@ where
         Self::check_preconditions(&stack);
         Runtime {
             config: self.config,
+            wasm_module_cache: Arc::clone(&self.wasm_module_cache),
             memory: self.memory.clone(),
             module: self.module.clone(),
             host_buffer: None,
@@ -2198,7 +2204,10 @@ where
                 None => return Err(Error::KeyNotFound(key)),
             };
 
-            parity_wasm::deserialize_buffer(contract_wasm.bytes())?
+            self.wasm_module_cache.get_or_insert_with(
+                contract_wasm.bytes(),
+                parity_wasm::deserialize_buffer::<Module>,
+            )?
         };
 
         let entry_point_name = entry_point.name();
@@ -2252,7 +2261,14 @@ where
 
         stack.push(call_stack_element)?;
 
-        let mut runtime = Runtime::new(config, memory, module, context, stack);
+        let mut runtime = Runtime::new(
+            config,
+            Arc::clone(&self.wasm_module_cache),
+            memory,
+            module,
+            context,
+            stack,
+        );
 
         let result = instance.invoke_export(entry_point_name, &[], &mut runtime);
 
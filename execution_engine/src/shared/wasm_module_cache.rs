@@ -0,0 +1,213 @@
+//! An LRU cache of the Wasm modules of stored contracts.
+//!
+//! The Wasm of a stored contract is preprocessed once when the contract is installed, so calling
+//! it only requires the stored bytes to be parsed.  Caching the parsed modules lets repeated calls
+//! to popular contracts skip this step.  The stored bytes are still read from global state on
+//! every call, so execution effects don't depend on the contents of the cache.
+//!
+//! Modules are keyed by a digest of the bytes they were parsed from rather than by their
+//! `ContractWasmHash`.  The bytes stored under a `ContractWasmHash` can be replaced, e.g. by a
+//! global state update, so keying by content keeps a module from being reused for different bytes
+//! when executing against state roots from either side of such a change.
+use std::sync::{Mutex, MutexGuard};
+
+use linked_hash_map::LinkedHashMap;
+use parity_wasm::elements::Module;
+
+use casper_hashing::Digest;
+
+/// Default value for the maximum number of modules held by a [`WasmModuleCache`].
+pub const DEFAULT_WASM_MODULE_CACHE_SIZE: usize = 256;
+
+#[derive(Debug, Default)]
+struct Inner {
+    modules: LinkedHashMap<Digest, Module>,
+    hits: u64,
+    misses: u64,
+}
+
+/// Holds the parsed Wasm modules of stored contracts, keyed by a digest of their bytes.
+///
+/// Above `max_entries` modules, the least-recently-used module is evicted.  A cache with
+/// `max_entries` of `0` never holds any modules.
+#[derive(Debug)]
+pub struct WasmModuleCache {
+    max_entries: usize,
+    inner: Mutex<Inner>,
+}
+
+impl WasmModuleCache {
+    /// Creates an empty cache holding at most `max_entries` modules.
+    pub fn new(max_entries: usize) -> Self {
+        WasmModuleCache {
+            max_entries,
+            inner: Mutex::new(Inner::default()),
+        }
+    }
+
+    /// Returns the module cached for `wasm_bytes`, or caches and returns the module `parse`
+    /// produces from them if there is none.
+    pub fn get_or_insert_with<F, E>(&self, wasm_bytes: &[u8], parse: F) -> Result<Module, E>
+    where
+        F: FnOnce(&[u8]) -> Result<Module, E>,
+    {
+        let wasm_digest = Digest::hash(wasm_bytes);
+        {
+            let mut guard = self.lock();
+            let inner = &mut *guard;
+            if let Some(module) = inner.modules.get_refresh(&wasm_digest) {
+                inner.hits += 1;
+                return Ok(module.clone());
+            }
+            inner.misses += 1;
+        }
+
+        let module = parse(wasm_bytes)?;
+
+        if self.max_entries > 0 {
+            let mut inner = self.lock();
+            inner.modules.insert(wasm_digest, module.clone());
+            while inner.modules.len() > self.max_entries {
+                inner.modules.pop_front();
+            }
+        }
+        Ok(module)
+    }
+
+    /// Evicts all modules.
+    pub fn clear(&self) {
+        self.lock().modules.clear();
+    }
+
+    /// Returns the maximum number of modules held by the cache.
+    pub fn max_entries(&self) -> usize {
+        self.max_entries
+    }
+
+    /// Returns the number of modules currently held by the cache.
+    pub fn len(&self) -> usize {
+        self.lock().modules.len()
+    }
+
+    /// Returns `true` if the cache holds no modules.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of lookups which were served from the cache.
+    pub fn hits(&self) -> u64 {
+        self.lock().hits
+    }
+
+    /// Returns the number of lookups which had to load the module.
+    pub fn misses(&self) -> u64 {
+        self.lock().misses
+    }
+
+    fn lock(&self) -> MutexGuard<Inner> {
+        self.inner.lock().expect("wasm module cache lock poisoned")
+    }
+}
+
+impl Default for WasmModuleCache {
+    fn default() -> Self {
+        WasmModuleCache::new(DEFAULT_WASM_MODULE_CACHE_SIZE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(parses: &mut usize) -> Result<Module, ()> {
+        *parses += 1;
+        Ok(Module::default())
+    }
+
+    #[test]
+    fn should_parse_each_module_once() {
+        let cache = WasmModuleCache::new(2);
+        let mut parses = 0;
+
+        cache
+            .get_or_insert_with(&[1], |_| parse(&mut parses))
+            .unwrap();
+        cache
+            .get_or_insert_with(&[1], |_| parse(&mut parses))
+            .unwrap();
+
+        assert_eq!(parses, 1);
+        assert_eq!(cache.hits(), 1);
+        assert_eq!(cache.misses(), 1);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn should_parse_again_when_bytes_change() {
+        let cache = WasmModuleCache::new(2);
+        let mut parses = 0;
+
+        // The same contract wasm hash can hold different bytes under different state roots.
+        cache
+            .get_or_insert_with(&[1], |_| parse(&mut parses))
+            .unwrap();
+        cache
+            .get_or_insert_with(&[2], |_| parse(&mut parses))
+            .unwrap();
+        assert_eq!(parses, 2);
+
+        cache
+            .get_or_insert_with(&[1], |_| parse(&mut parses))
+            .unwrap();
+        assert_eq!(parses, 2);
+    }
+
+    #[test]
+    fn should_evict_least_recently_used_module() {
+        let cache = WasmModuleCache::new(2);
+        let (first, second, third): (&[u8], &[u8], &[u8]) = (&[1], &[2], &[3]);
+        let mut parses = 0;
+
+        cache
+            .get_or_insert_with(first, |_| parse(&mut parses))
+            .unwrap();
+        cache
+            .get_or_insert_with(second, |_| parse(&mut parses))
+            .unwrap();
+        // Refresh `first`, leaving `second` as the least recently used module.
+        cache
+            .get_or_insert_with(first, |_| parse(&mut parses))
+            .unwrap();
+        cache
+            .get_or_insert_with(third, |_| parse(&mut parses))
+            .unwrap();
+        assert_eq!(parses, 3);
+        assert_eq!(cache.len(), 2);
+
+        cache
+            .get_or_insert_with(first, |_| parse(&mut parses))
+            .unwrap();
+        assert_eq!(parses, 3);
+        cache
+            .get_or_insert_with(second, |_| parse(&mut parses))
+            .unwrap();
+        assert_eq!(parses, 4);
+    }
+
+    #[test]
+    fn should_not_cache_when_disabled() {
+        let cache = WasmModuleCache::new(0);
+        let mut parses = 0;
+
+        cache
+            .get_or_insert_with(&[1], |_| parse(&mut parses))
+            .unwrap();
+        cache
+            .get_or_insert_with(&[1], |_| parse(&mut parses))
+            .unwrap();
+
+        assert_eq!(parses, 2);
+        assert!(cache.is_empty());
+        assert_eq!(cache.hits(), 0);
+    }
+}
@@ -4,7 +4,7 @@ use num_rational::Ratio;
 
 use casper_execution_engine::core::engine_state::UpgradeConfig;
 use casper_hashing::Digest;
-use casper_types::{EraId, Key, ProtocolVersion, StoredValue};
+use casper_types::{CLValue, EraId, Key, ProtocolVersion, StoredValue};
 
 /// Builds an `UpgradeConfig`.
 #[derive(Default)]
@@ -19,6 +19,11 @@ pub struct UpgradeRequestBuilder {
     new_round_seigniorage_rate: Option<Ratio<u64>>,
     new_unbonding_delay: Option<u64>,
     global_state_update: BTreeMap<Key, StoredValue>,
+    disabled_versions_retention: Option<u32>,
+    network_name: Option<String>,
+    new_config_flags: Option<BTreeMap<String, CLValue>>,
+    global_state_update_notes: BTreeMap<Key, String>,
+    strict_global_state_update: bool,
 }
 
 impl UpgradeRequestBuilder {
@@ -87,6 +92,39 @@ impl UpgradeRequestBuilder {
         self
     }
 
+    /// Sets `global_state_update_notes`.
+    pub fn with_global_state_update_notes(
+        mut self,
+        global_state_update_notes: BTreeMap<Key, String>,
+    ) -> Self {
+        self.global_state_update_notes = global_state_update_notes;
+        self
+    }
+
+    /// Sets `strict_global_state_update`.
+    pub fn with_strict_global_state_update(mut self, strict_global_state_update: bool) -> Self {
+        self.strict_global_state_update = strict_global_state_update;
+        self
+    }
+
+    /// Sets `disabled_versions_retention`.
+    pub fn with_disabled_versions_retention(mut self, disabled_versions_retention: u32) -> Self {
+        self.disabled_versions_retention = Some(disabled_versions_retention);
+        self
+    }
+
+    /// Sets `network_name`.
+    pub fn with_network_name(mut self, network_name: &str) -> Self {
+        self.network_name = Some(network_name.to_string());
+        self
+    }
+
+    /// Sets `new_config_flags`.
+    pub fn with_new_config_flags(mut self, new_config_flags: BTreeMap<String, CLValue>) -> Self {
+        self.new_config_flags = Some(new_config_flags);
+        self
+    }
+
     /// Sets `activation_point`.
     pub fn with_activation_point(mut self, activation_point: EraId) -> Self {
         self.activation_point = Some(activation_point);
@@ -95,8 +133,8 @@ impl UpgradeRequestBuilder {
 
     /// Consumes the `UpgradeRequestBuilder` and returns an [`UpgradeConfig`].
     pub fn build(self) -> UpgradeConfig {
-        UpgradeConfig::new(
-            self.pre_state_hash,
+        let mut upgrade_config = UpgradeConfig::new(
+            self.pre_state_hash.into(),
             self.current_protocol_version,
             self.new_protocol_version,
             self.activation_point,
@@ -106,6 +144,12 @@ impl UpgradeRequestBuilder {
             self.new_round_seigniorage_rate,
             self.new_unbonding_delay,
             self.global_state_update,
-        )
+            self.disabled_versions_retention,
+            self.network_name,
+            self.new_config_flags,
+        );
+        upgrade_config.with_global_state_update_notes(self.global_state_update_notes);
+        upgrade_config.with_strict_global_state_update(self.strict_global_state_update);
+        upgrade_config
     }
 }
@@ -22,8 +22,9 @@ use casper_execution_engine::{
             execution_result::ExecutionResult,
             run_genesis_request::RunGenesisRequest,
             step::{StepRequest, StepSuccess},
-            BalanceResult, EngineConfig, EngineState, GenesisSuccess, GetBidsRequest, QueryRequest,
-            QueryResult, StepError, SystemContractRegistry, UpgradeConfig, UpgradeSuccess,
+            BalanceResult, EngineConfig, EngineState, GenesisSuccess, GetBidsRequest, Migration,
+            QueryRequest, QueryResult, StepError, SystemContractRegistry, UpgradeConfig,
+            UpgradeSuccess,
         },
         execution,
     },
@@ -57,7 +58,7 @@ use casper_types::{
     },
     CLTyped, CLValue, Contract, ContractHash, ContractPackage, ContractPackageHash, ContractWasm,
     DeployHash, DeployInfo, EraId, Gas, Key, KeyTag, PublicKey, RuntimeArgs, StoredValue, Transfer,
-    TransferAddr, URef, U512,
+    TransferAddr, TransformEntry, URef, U512,
 };
 
 use crate::{
@@ -504,23 +505,39 @@ where
         &mut self,
         engine_config: EngineConfig,
         upgrade_config: &mut UpgradeConfig,
+    ) -> &mut Self {
+        self.upgrade_with_upgrade_request_and_migrations(engine_config, upgrade_config, &[])
+    }
+
+    /// Upgrades the execution engine, running the given migrations as part of the upgrade.
+    pub fn upgrade_with_upgrade_request_and_migrations(
+        &mut self,
+        engine_config: EngineConfig,
+        upgrade_config: &mut UpgradeConfig,
+        migrations: &[Box<dyn Migration>],
     ) -> &mut Self {
         let pre_state_hash = self.post_state_hash.expect("should have state hash");
-        upgrade_config.with_pre_state_hash(pre_state_hash);
+        upgrade_config.with_pre_state_hash(pre_state_hash.into());
 
         let engine_state = Rc::get_mut(&mut self.engine_state).unwrap();
         engine_state.update_config(engine_config);
 
-        let result = self
-            .engine_state
-            .commit_upgrade(CorrelationId::new(), upgrade_config.clone());
+        let result = upgrade_config
+            .validate()
+            .map_err(engine_state::Error::ProtocolUpgrade)
+            .and_then(|validated_upgrade_config| {
+                self.engine_state.commit_upgrade_with_migrations(
+                    CorrelationId::new(),
+                    validated_upgrade_config,
+                    migrations,
+                )
+            });
 
         if let Ok(UpgradeSuccess {
-            post_state_hash,
-            execution_effect: _,
+            post_state_hash, ..
         }) = result
         {
-            self.post_state_hash = Some(post_state_hash);
+            self.post_state_hash = Some(post_state_hash.into());
         }
 
         self.upgrade_results.push(result);
@@ -726,6 +743,31 @@ where
         self
     }
 
+    /// Returns the transforms of the last upgrade as entries sorted by key.
+    ///
+    /// The entries are serializable and independent of the order in which the upgrade produced
+    /// them, so they can be compared against a stored snapshot of the expected upgrade effects.
+    pub fn get_upgrade_snapshot(&self) -> Vec<TransformEntry> {
+        let result = self
+            .upgrade_results
+            .last()
+            .expect("Expected to be called after a system upgrade.");
+        let upgrade_success = result
+            .as_ref()
+            .unwrap_or_else(|_| panic!("Expected success, got: {:?}", result));
+
+        let mut transforms: Vec<(&Key, &Transform)> =
+            upgrade_success.execution_effect.transforms.iter().collect();
+        transforms.sort_by_key(|(key, _)| **key);
+        transforms
+            .into_iter()
+            .map(|(key, transform)| TransformEntry {
+                key: key.to_formatted_string(),
+                transform: transform.into(),
+            })
+            .collect()
+    }
+
     /// Returns the "handle payment" contract, panics if it can't be found.
     pub fn get_handle_payment_contract(&self) -> Contract {
         let handle_payment_contract: Key = self
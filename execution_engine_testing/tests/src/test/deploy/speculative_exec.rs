@@ -0,0 +1,58 @@
+use casper_engine_test_support::{
+    DeployItemBuilder, InMemoryWasmTestBuilder, DEFAULT_ACCOUNT_ADDR, DEFAULT_BLOCK_TIME,
+    DEFAULT_PROPOSER_PUBLIC_KEY, DEFAULT_PROTOCOL_VERSION, DEFAULT_RUN_GENESIS_REQUEST,
+};
+use casper_execution_engine::shared::{
+    newtypes::CorrelationId, system_config::DEFAULT_WASMLESS_TRANSFER_COST,
+};
+use casper_types::{account::AccountHash, runtime_args, system::mint, Gas, RuntimeArgs, U512};
+
+const ACCOUNT_1_ADDR: AccountHash = AccountHash::new([42; 32]);
+
+#[ignore]
+#[test]
+fn should_run_speculative_exec_without_committing() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    let state_root_hash = builder.get_post_state_hash();
+    let main_purse = builder
+        .get_account(*DEFAULT_ACCOUNT_ADDR)
+        .expect("should get account")
+        .main_purse();
+    let balance_before = builder.get_purse_balance(main_purse);
+
+    let deploy_item = DeployItemBuilder::new()
+        .with_address(*DEFAULT_ACCOUNT_ADDR)
+        .with_empty_payment_bytes(runtime_args! {})
+        .with_transfer_args(runtime_args! {
+            mint::ARG_TARGET => ACCOUNT_1_ADDR,
+            mint::ARG_AMOUNT => U512::from(1_000_000_000u64),
+            mint::ARG_ID => Some(1u64)
+        })
+        .with_authorization_keys(&[*DEFAULT_ACCOUNT_ADDR])
+        .build();
+
+    let execution_result = builder
+        .get_engine_state()
+        .run_speculative_exec(
+            CorrelationId::new(),
+            state_root_hash,
+            DEFAULT_BLOCK_TIME,
+            *DEFAULT_PROTOCOL_VERSION,
+            deploy_item,
+            DEFAULT_PROPOSER_PUBLIC_KEY.clone(),
+        )
+        .expect("should run speculative exec");
+
+    assert!(execution_result.is_success());
+    assert_eq!(
+        execution_result.cost(),
+        Gas::from(DEFAULT_WASMLESS_TRANSFER_COST)
+    );
+    assert!(!execution_result.execution_journal().is_empty());
+
+    assert_eq!(builder.get_post_state_hash(), state_root_hash);
+    assert_eq!(builder.get_purse_balance(main_purse), balance_before);
+    assert!(builder.get_account(ACCOUNT_1_ADDR).is_none());
+}
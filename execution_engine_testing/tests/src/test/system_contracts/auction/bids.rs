@@ -11,12 +11,16 @@ use casper_engine_test_support::{
     DEFAULT_RUN_GENESIS_REQUEST, DEFAULT_UNBONDING_DELAY, MINIMUM_ACCOUNT_CREATION_BALANCE,
     SYSTEM_ADDR, TIMESTAMP_MILLIS_INCREMENT,
 };
-use casper_execution_engine::core::{
-    engine_state::{
-        self,
-        genesis::{GenesisAccount, GenesisValidator},
+use casper_execution_engine::{
+    core::{
+        engine_state::{
+            self,
+            genesis::{GenesisAccount, GenesisValidator},
+            GetEraValidatorsRangeRequest,
+        },
+        execution,
     },
-    execution,
+    shared::newtypes::CorrelationId,
 };
 use casper_types::{
     self,
@@ -3058,3 +3062,45 @@ fn should_fail_with_more_accounts_than_slots() {
 fn should_run_genesis_with_exact_validator_slots() {
     check_validator_slots_for_accounts(DEFAULT_EXEC_CONFIG.validator_slots() as usize);
 }
+
+#[ignore]
+#[test]
+fn should_get_era_validators_range_from_snapshot() {
+    let accounts = {
+        let mut tmp: Vec<GenesisAccount> = DEFAULT_ACCOUNTS.clone();
+        let account_1 = GenesisAccount::account(
+            ACCOUNT_1_PK.clone(),
+            Motes::new(ACCOUNT_1_BALANCE.into()),
+            Some(GenesisValidator::new(
+                Motes::new(ACCOUNT_1_BOND.into()),
+                DelegationRate::zero(),
+            )),
+        );
+        tmp.push(account_1);
+        tmp
+    };
+    let run_genesis_request = utils::create_run_genesis_request(accounts);
+
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&run_genesis_request);
+
+    let era_validators: EraValidators = builder.get_era_validators();
+    let state_hash = builder.get_post_state_hash();
+    let get_era_validators_range = |start_era_id: EraId, end_era_id: EraId| {
+        let request = GetEraValidatorsRangeRequest::new(state_hash, start_era_id, end_era_id);
+        builder
+            .get_engine_state()
+            .get_era_validators_range(CorrelationId::new(), request)
+            .expect("should get era validators range")
+    };
+
+    let all_eras = get_era_validators_range(INITIAL_ERA_ID, EraId::new(u64::MAX));
+    assert_eq!(all_eras, era_validators);
+
+    let single_era = get_era_validators_range(INITIAL_ERA_ID, INITIAL_ERA_ID);
+    assert_eq!(single_era.len(), 1);
+    assert_eq!(single_era[&INITIAL_ERA_ID], era_validators[&INITIAL_ERA_ID]);
+
+    let beyond_snapshot = EraId::from(DEFAULT_AUCTION_DELAY).successor().successor();
+    assert!(get_era_validators_range(beyond_snapshot, EraId::new(u64::MAX)).is_empty());
+}
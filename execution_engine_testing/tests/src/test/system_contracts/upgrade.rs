@@ -1,17 +1,24 @@
-use std::collections::BTreeMap;
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
 
 use num_rational::Ratio;
 
 use casper_engine_test_support::{
     ExecuteRequestBuilder, InMemoryWasmTestBuilder, UpgradeRequestBuilder, DEFAULT_ACCOUNT_ADDR,
-    DEFAULT_MAX_ASSOCIATED_KEYS, DEFAULT_RUN_GENESIS_REQUEST, DEFAULT_UNBONDING_DELAY,
-    DEFAULT_WASM_CONFIG,
+    DEFAULT_AUCTION_DELAY, DEFAULT_MAX_ASSOCIATED_KEYS, DEFAULT_RUN_GENESIS_REQUEST,
+    DEFAULT_UNBONDING_DELAY, DEFAULT_VALIDATOR_SLOTS, DEFAULT_WASM_CONFIG,
 };
 
 use casper_execution_engine::{
     core::engine_state::{
-        EngineConfig, DEFAULT_MAX_QUERY_DEPTH, DEFAULT_MAX_RUNTIME_CALL_STACK_HEIGHT,
+        self,
+        upgrade::{self, ProtocolUpgradeError},
+        ConfigFieldChange, EngineConfig, Migration, SystemContractRegistry, UpgradeConfig,
+        UpgradeSuccess, DEFAULT_MAX_QUERY_DEPTH, DEFAULT_MAX_RUNTIME_CALL_STACK_HEIGHT,
     },
+    shared::newtypes::CorrelationId,
     shared::{
         host_function_costs::HostFunctionCosts,
         opcode_costs::{
@@ -28,9 +35,11 @@ use casper_execution_engine::{
             mint_costs::MintCosts, standard_payment_costs::StandardPaymentCosts, SystemConfig,
             DEFAULT_WASMLESS_TRANSFER_COST,
         },
+        transform::Transform,
         wasm_config::{WasmConfig, DEFAULT_MAX_STACK_HEIGHT, DEFAULT_WASM_MAX_MEMORY},
     },
 };
+use casper_hashing::Digest;
 use casper_types::{
     account::{AccountHash, ACCOUNT_HASH_LENGTH},
     runtime_args,
@@ -40,7 +49,7 @@ use casper_types::{
         },
         mint::ROUND_SEIGNIORAGE_RATE_KEY,
     },
-    CLValue, EraId, ProtocolVersion, RuntimeArgs, StoredValue, U256, U512,
+    CLValue, EraId, Key, ProtocolVersion, RuntimeArgs, StoredValue, U256, U512,
 };
 
 const PROTOCOL_VERSION: ProtocolVersion = ProtocolVersion::V1_0_0;
@@ -300,6 +309,374 @@ fn should_allow_skip_minor_versions() {
     assert!(!maybe_upgrade_result.is_err(), "expected success");
 }
 
+#[ignore]
+#[test]
+fn should_skip_no_op_upgrade() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    let pre_state_hash = builder.get_post_state_hash();
+
+    let sem_ver = PROTOCOL_VERSION.value();
+    let new_protocol_version =
+        ProtocolVersion::from_parts(sem_ver.major, sem_ver.minor, sem_ver.patch + 1);
+
+    let mut upgrade_request = {
+        UpgradeRequestBuilder::new()
+            .with_current_protocol_version(PROTOCOL_VERSION)
+            .with_new_protocol_version(new_protocol_version)
+            .with_activation_point(DEFAULT_ACTIVATION_POINT)
+            .build()
+    };
+
+    builder
+        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request)
+        .expect_upgrade_success();
+
+    let upgrade_success = builder
+        .get_upgrade_result(0)
+        .expect("should have upgrade result")
+        .as_ref()
+        .expect("should have upgraded");
+
+    assert_eq!(
+        Digest::from(upgrade_success.post_state_hash),
+        pre_state_hash
+    );
+    assert!(upgrade_success.execution_effect.transforms.is_empty());
+    assert_eq!(upgrade_success.applied_transforms().count(), 0);
+}
+
+fn query_u32(builder: &InMemoryWasmTestBuilder, post_state_hash: Digest, key: Key) -> u32 {
+    builder
+        .query(Some(post_state_hash), key, &[])
+        .expect("should have value")
+        .as_cl_value()
+        .expect("should be CLValue")
+        .clone()
+        .into_t()
+        .expect("should be u32")
+}
+
+fn make_chained_upgrade_configs(
+    builder: &InMemoryWasmTestBuilder,
+    second_validator_slots: u32,
+) -> Vec<UpgradeConfig> {
+    let sem_ver = PROTOCOL_VERSION.value();
+    let patch_version =
+        ProtocolVersion::from_parts(sem_ver.major, sem_ver.minor, sem_ver.patch + 1);
+    let major_version =
+        ProtocolVersion::from_parts(sem_ver.major + 1, sem_ver.minor, sem_ver.patch + 1);
+
+    let mut first_upgrade_config = UpgradeRequestBuilder::new()
+        .with_current_protocol_version(PROTOCOL_VERSION)
+        .with_new_protocol_version(patch_version)
+        .with_activation_point(DEFAULT_ACTIVATION_POINT)
+        .with_new_validator_slots(DEFAULT_VALIDATOR_SLOTS + 1)
+        .build();
+    first_upgrade_config.with_pre_state_hash(builder.get_post_state_hash().into());
+
+    let second_upgrade_config = UpgradeRequestBuilder::new()
+        .with_current_protocol_version(patch_version)
+        .with_new_protocol_version(major_version)
+        .with_activation_point(DEFAULT_ACTIVATION_POINT)
+        .with_new_validator_slots(second_validator_slots)
+        .build();
+
+    vec![first_upgrade_config, second_upgrade_config]
+}
+
+#[ignore]
+#[test]
+fn should_apply_chained_upgrades_together() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    let validator_slots_key = builder
+        .get_contract(builder.get_auction_contract_hash())
+        .expect("auction should exist")
+        .named_keys()[VALIDATOR_SLOTS_KEY];
+
+    let upgrade_configs = make_chained_upgrade_configs(&builder, DEFAULT_VALIDATOR_SLOTS + 2);
+    let upgrade_success = builder
+        .get_engine_state()
+        .apply_upgrades(CorrelationId::new(), upgrade_configs)
+        .expect("should apply upgrades");
+    let post_state_hash = Digest::from(upgrade_success.post_state_hash);
+
+    assert_eq!(
+        query_u32(&builder, post_state_hash, validator_slots_key),
+        DEFAULT_VALIDATOR_SLOTS + 2
+    );
+    let mint_contract = builder
+        .query(
+            Some(post_state_hash),
+            Key::from(builder.get_mint_contract_hash()),
+            &[],
+        )
+        .expect("should have mint contract")
+        .as_contract()
+        .cloned()
+        .expect("should be contract");
+    assert_eq!(
+        mint_contract.protocol_version().value().major,
+        PROTOCOL_VERSION.value().major + 1
+    );
+}
+
+#[ignore]
+#[test]
+fn should_commit_nothing_when_a_chained_upgrade_fails() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    // Applying the first upgrade alone on an identical chain gives the state it would commit.
+    let first_post_state_hash = {
+        let mut other_builder = InMemoryWasmTestBuilder::default();
+        other_builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+        let mut first_upgrade_config =
+            make_chained_upgrade_configs(&other_builder, DEFAULT_VALIDATOR_SLOTS).swap_remove(0);
+        other_builder
+            .upgrade_with_upgrade_request(
+                *other_builder.get_engine_state().config(),
+                &mut first_upgrade_config,
+            )
+            .expect_upgrade_success();
+        other_builder.get_post_state_hash()
+    };
+
+    // The second upgrade passes validation, but fails while it's applied: after the system
+    // contracts have been upgraded and the validator slots written, its strict global state
+    // update turns out to target the mint contract.
+    let pre_state_hash = builder.get_post_state_hash();
+    let mint_key = Key::from(builder.get_mint_contract_hash());
+    let mint_contract = builder
+        .query(None, mint_key, &[])
+        .expect("should have mint contract");
+    let mut upgrade_configs = make_chained_upgrade_configs(&builder, DEFAULT_VALIDATOR_SLOTS + 2);
+    let mut global_state_update = BTreeMap::new();
+    global_state_update.insert(mint_key, mint_contract);
+    upgrade_configs[1] = UpgradeRequestBuilder::new()
+        .with_current_protocol_version(upgrade_configs[1].current_protocol_version())
+        .with_new_protocol_version(upgrade_configs[1].new_protocol_version())
+        .with_activation_point(DEFAULT_ACTIVATION_POINT)
+        .with_new_validator_slots(DEFAULT_VALIDATOR_SLOTS + 2)
+        .with_global_state_update(global_state_update)
+        .with_strict_global_state_update(true)
+        .build();
+    assert!(upgrade_configs[1].validate().is_ok());
+
+    let result = builder
+        .get_engine_state()
+        .apply_upgrades(CorrelationId::new(), upgrade_configs);
+    assert!(
+        matches!(
+            result,
+            Err(engine_state::Error::ProtocolUpgrade(
+                ProtocolUpgradeError::UpdateTargetsSystemContract(key)
+            )) if key == mint_key
+        ),
+        "expected failure got {:?}",
+        result
+    );
+
+    // Neither upgrade was committed: the state left by the first upgrade doesn't exist, and the
+    // mint contract is still at the original protocol version.
+    assert!(builder
+        .get_engine_state()
+        .tracking_copy(first_post_state_hash)
+        .expect("should check for state")
+        .is_none());
+    let mint_contract = builder
+        .query(Some(pre_state_hash), mint_key, &[])
+        .expect("should have mint contract")
+        .as_contract()
+        .cloned()
+        .expect("should be contract");
+    assert_eq!(mint_contract.protocol_version(), PROTOCOL_VERSION);
+}
+
+#[ignore]
+#[test]
+fn should_notify_upgrade_observer_once() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    let observed_post_state_hashes = Arc::new(Mutex::new(Vec::new()));
+    let observed = Arc::clone(&observed_post_state_hashes);
+    builder
+        .get_engine_state()
+        .register_upgrade_observer(Arc::new(move |upgrade_success| {
+            observed
+                .lock()
+                .unwrap()
+                .push(Digest::from(upgrade_success.post_state_hash))
+        }));
+
+    let sem_ver = PROTOCOL_VERSION.value();
+    let new_protocol_version =
+        ProtocolVersion::from_parts(sem_ver.major, sem_ver.minor, sem_ver.patch + 1);
+
+    let mut upgrade_request = {
+        UpgradeRequestBuilder::new()
+            .with_current_protocol_version(PROTOCOL_VERSION)
+            .with_new_protocol_version(new_protocol_version)
+            .with_activation_point(DEFAULT_ACTIVATION_POINT)
+            .with_new_validator_slots(DEFAULT_VALIDATOR_SLOTS + 1)
+            .build()
+    };
+
+    builder
+        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request)
+        .expect_upgrade_success();
+
+    assert_eq!(
+        *observed_post_state_hashes.lock().unwrap(),
+        vec![builder.get_post_state_hash()]
+    );
+}
+
+#[ignore]
+#[test]
+fn should_upgrade_config_flag() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    let sem_ver = PROTOCOL_VERSION.value();
+    let new_protocol_version =
+        ProtocolVersion::from_parts(sem_ver.major, sem_ver.minor, sem_ver.patch + 1);
+
+    let mut new_config_flags = BTreeMap::new();
+    new_config_flags.insert(
+        upgrade::GENESIS_TIMESTAMP_SEMANTICS_FLAG.to_string(),
+        CLValue::from_t(true).unwrap(),
+    );
+
+    let mut upgrade_request = {
+        UpgradeRequestBuilder::new()
+            .with_current_protocol_version(PROTOCOL_VERSION)
+            .with_new_protocol_version(new_protocol_version)
+            .with_activation_point(DEFAULT_ACTIVATION_POINT)
+            .with_new_config_flags(new_config_flags)
+            .build()
+    };
+
+    builder
+        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request)
+        .expect_upgrade_success();
+
+    let flag: bool = builder
+        .query(
+            None,
+            upgrade::config_flag_key(upgrade::GENESIS_TIMESTAMP_SEMANTICS_FLAG),
+            &[],
+        )
+        .expect("should have config flag")
+        .as_cl_value()
+        .expect("should be CLValue")
+        .clone()
+        .into_t()
+        .expect("should be bool");
+    assert!(flag);
+}
+
+#[ignore]
+#[test]
+fn should_not_upgrade_unknown_config_flag() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    let sem_ver = PROTOCOL_VERSION.value();
+    let new_protocol_version =
+        ProtocolVersion::from_parts(sem_ver.major, sem_ver.minor, sem_ver.patch + 1);
+
+    let mut new_config_flags = BTreeMap::new();
+    new_config_flags.insert("unknown_flag".to_string(), CLValue::from_t(true).unwrap());
+
+    let mut upgrade_request = {
+        UpgradeRequestBuilder::new()
+            .with_current_protocol_version(PROTOCOL_VERSION)
+            .with_new_protocol_version(new_protocol_version)
+            .with_activation_point(DEFAULT_ACTIVATION_POINT)
+            .with_new_config_flags(new_config_flags)
+            .build()
+    };
+
+    builder
+        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request);
+
+    let maybe_upgrade_result = builder.get_upgrade_result(0).expect("should have response");
+    assert!(
+        matches!(
+            maybe_upgrade_result,
+            Err(engine_state::Error::ProtocolUpgrade(
+                ProtocolUpgradeError::UnknownConfigFlag(_)
+            ))
+        ),
+        "expected failure got {:?}",
+        maybe_upgrade_result
+    );
+}
+
+fn upgrade_with_update_targeting_mint(strict: bool) -> Result<UpgradeSuccess, engine_state::Error> {
+    let mut builder = InMemoryWasmTestBuilder::default();
+
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    let sem_ver = PROTOCOL_VERSION.value();
+    let new_protocol_version =
+        ProtocolVersion::from_parts(sem_ver.major, sem_ver.minor, sem_ver.patch + 1);
+
+    let mint_key = Key::from(builder.get_mint_contract_hash());
+    let mint = builder
+        .query(None, mint_key, &[])
+        .expect("should have mint contract");
+    let mut global_state_update = BTreeMap::new();
+    global_state_update.insert(mint_key, mint);
+
+    let mut upgrade_request = {
+        UpgradeRequestBuilder::new()
+            .with_current_protocol_version(PROTOCOL_VERSION)
+            .with_new_protocol_version(new_protocol_version)
+            .with_activation_point(DEFAULT_ACTIVATION_POINT)
+            .with_global_state_update(global_state_update)
+            .with_strict_global_state_update(strict)
+            .build()
+    };
+
+    builder
+        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request);
+
+    builder
+        .get_upgrade_result(0)
+        .expect("should have response")
+        .clone()
+}
+
+#[ignore]
+#[test]
+fn should_reject_update_targeting_system_contract_only_when_strict() {
+    let upgrade_result = upgrade_with_update_targeting_mint(true);
+    assert!(
+        matches!(
+            upgrade_result,
+            Err(engine_state::Error::ProtocolUpgrade(
+                ProtocolUpgradeError::UpdateTargetsSystemContract(Key::Hash(_))
+            ))
+        ),
+        "expected failure got {:?}",
+        upgrade_result
+    );
+
+    upgrade_with_update_targeting_mint(false).expect("lenient upgrade should succeed");
+}
+
 #[ignore]
 #[test]
 fn should_upgrade_only_validator_slots() {
@@ -717,3 +1094,492 @@ fn should_increase_max_associated_keys_after_upgrade() {
         new_engine_config.max_associated_keys() as usize
     );
 }
+
+/// Doubles the `u64` stored under a single key.
+struct DoubleValueMigration(Key);
+
+impl Migration for DoubleValueMigration {
+    fn keys(&self) -> Vec<Key> {
+        vec![self.0]
+    }
+
+    fn migrate(&self, _key: &Key, value: StoredValue) -> Result<StoredValue, ProtocolUpgradeError> {
+        let value: u64 = value
+            .as_cl_value()
+            .cloned()
+            .ok_or_else(|| ProtocolUpgradeError::FailedMigration("not a CLValue".to_string()))?
+            .into_t()
+            .map_err(|error| ProtocolUpgradeError::FailedMigration(error.to_string()))?;
+        CLValue::from_t(value * 2)
+            .map(StoredValue::from)
+            .map_err(|error| ProtocolUpgradeError::FailedMigration(error.to_string()))
+    }
+}
+
+#[ignore]
+#[test]
+fn should_run_migrations_during_upgrade() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    let sem_ver = PROTOCOL_VERSION.value();
+    let new_protocol_version =
+        ProtocolVersion::from_parts(sem_ver.major, sem_ver.minor, sem_ver.patch + 1);
+
+    let unbonding_delay_key = builder
+        .get_contract(builder.get_auction_contract_hash())
+        .expect("auction should exist")
+        .named_keys()[UNBONDING_DELAY_KEY];
+
+    let mut upgrade_request = {
+        UpgradeRequestBuilder::new()
+            .with_current_protocol_version(PROTOCOL_VERSION)
+            .with_new_protocol_version(new_protocol_version)
+            .with_activation_point(DEFAULT_ACTIVATION_POINT)
+            .build()
+    };
+
+    let migrations: Vec<Box<dyn Migration>> =
+        vec![Box::new(DoubleValueMigration(unbonding_delay_key))];
+
+    builder
+        .upgrade_with_upgrade_request_and_migrations(
+            *builder.get_engine_state().config(),
+            &mut upgrade_request,
+            &migrations,
+        )
+        .expect_upgrade_success();
+
+    let after_unbonding_delay: u64 = builder
+        .query(None, unbonding_delay_key, &[])
+        .expect("should have unbonding delay")
+        .as_cl_value()
+        .expect("should be a CLValue")
+        .clone()
+        .into_t()
+        .expect("should be u64");
+
+    assert_eq!(
+        after_unbonding_delay,
+        DEFAULT_UNBONDING_DELAY * 2,
+        "Should have migrated unbonding delay"
+    );
+}
+
+#[ignore]
+#[test]
+fn should_apply_upgrade_phases_in_order() {
+    const CONFIG_UNBONDING_DELAY: u64 = DEFAULT_UNBONDING_DELAY + 1;
+    const UPDATED_UNBONDING_DELAY: u64 = DEFAULT_UNBONDING_DELAY + 2;
+
+    let mut builder = InMemoryWasmTestBuilder::default();
+
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    let sem_ver = PROTOCOL_VERSION.value();
+    let new_protocol_version =
+        ProtocolVersion::from_parts(sem_ver.major, sem_ver.minor, sem_ver.patch + 1);
+
+    let unbonding_delay_key = builder
+        .get_contract(builder.get_auction_contract_hash())
+        .expect("auction should exist")
+        .named_keys()[UNBONDING_DELAY_KEY];
+
+    // All three phases touch the same key: the config value is overwritten by the global state
+    // update, which is then doubled by the migration.
+    let mut global_state_update = BTreeMap::new();
+    global_state_update.insert(
+        unbonding_delay_key,
+        StoredValue::CLValue(CLValue::from_t(UPDATED_UNBONDING_DELAY).unwrap()),
+    );
+
+    let mut upgrade_request = {
+        UpgradeRequestBuilder::new()
+            .with_current_protocol_version(PROTOCOL_VERSION)
+            .with_new_protocol_version(new_protocol_version)
+            .with_activation_point(DEFAULT_ACTIVATION_POINT)
+            .with_new_unbonding_delay(CONFIG_UNBONDING_DELAY)
+            .with_global_state_update(global_state_update)
+            .build()
+    };
+
+    let migrations: Vec<Box<dyn Migration>> =
+        vec![Box::new(DoubleValueMigration(unbonding_delay_key))];
+
+    builder
+        .upgrade_with_upgrade_request_and_migrations(
+            *builder.get_engine_state().config(),
+            &mut upgrade_request,
+            &migrations,
+        )
+        .expect_upgrade_success();
+
+    let after_unbonding_delay: u64 = builder
+        .query(None, unbonding_delay_key, &[])
+        .expect("should have unbonding delay")
+        .as_cl_value()
+        .expect("should be a CLValue")
+        .clone()
+        .into_t()
+        .expect("should be u64");
+
+    assert_eq!(
+        after_unbonding_delay,
+        UPDATED_UNBONDING_DELAY * 2,
+        "Should have applied config, then global state update, then migrations"
+    );
+}
+
+fn get_system_contract_registry(builder: &InMemoryWasmTestBuilder) -> SystemContractRegistry {
+    builder
+        .query(None, Key::SystemContractRegistry, &[])
+        .expect("should have registry")
+        .as_cl_value()
+        .expect("should be a CLValue")
+        .clone()
+        .into_t()
+        .expect("should be a registry")
+}
+
+#[ignore]
+#[test]
+fn should_separate_system_contract_effect_from_emergency_writes() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    let registry = get_system_contract_registry(&builder);
+
+    let sem_ver = PROTOCOL_VERSION.value();
+    let new_protocol_version =
+        ProtocolVersion::from_parts(sem_ver.major + 1, sem_ver.minor, sem_ver.patch);
+
+    let emergency_key = Key::Hash([42; 32]);
+    let mut global_state_update = BTreeMap::new();
+    global_state_update.insert(
+        emergency_key,
+        StoredValue::CLValue(CLValue::from_t(42u64).unwrap()),
+    );
+
+    let mut upgrade_request = {
+        UpgradeRequestBuilder::new()
+            .with_current_protocol_version(PROTOCOL_VERSION)
+            .with_new_protocol_version(new_protocol_version)
+            .with_activation_point(DEFAULT_ACTIVATION_POINT)
+            .with_global_state_update(global_state_update)
+            .build()
+    };
+
+    builder
+        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request)
+        .expect_upgrade_success();
+
+    let system_contract_effect = builder
+        .get_upgrade_result(0)
+        .expect("should have upgrade result")
+        .as_ref()
+        .expect("should have upgraded")
+        .system_contract_effect(&registry);
+
+    let mut written_contract_keys: Vec<Key> = system_contract_effect
+        .transforms
+        .iter()
+        .filter(|(_, transform)| matches!(transform, Transform::Write(StoredValue::Contract(_))))
+        .map(|(key, _)| *key)
+        .collect();
+    written_contract_keys.sort();
+    let mut system_contract_keys: Vec<Key> = registry.values().copied().map(Key::from).collect();
+    system_contract_keys.sort();
+    assert_eq!(written_contract_keys, system_contract_keys);
+
+    assert!(system_contract_effect
+        .transforms
+        .get(&emergency_key)
+        .is_none());
+}
+
+#[ignore]
+#[test]
+fn should_predict_registry_after_major_upgrade() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    let sem_ver = PROTOCOL_VERSION.value();
+    let new_protocol_version =
+        ProtocolVersion::from_parts(sem_ver.major + 1, sem_ver.minor, sem_ver.patch);
+
+    let registry_before = get_system_contract_registry(&builder);
+    let predicted_registry = upgrade::predicted_system_contract_registry(&registry_before);
+
+    let mut upgrade_request = {
+        UpgradeRequestBuilder::new()
+            .with_current_protocol_version(PROTOCOL_VERSION)
+            .with_new_protocol_version(new_protocol_version)
+            .with_activation_point(DEFAULT_ACTIVATION_POINT)
+            .build()
+    };
+
+    builder
+        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request)
+        .expect_upgrade_success();
+
+    assert_eq!(predicted_registry, get_system_contract_registry(&builder));
+}
+
+#[ignore]
+#[test]
+fn should_snapshot_major_upgrade_effects() {
+    let sem_ver = PROTOCOL_VERSION.value();
+    let new_protocol_version =
+        ProtocolVersion::from_parts(sem_ver.major + 1, sem_ver.minor, sem_ver.patch);
+
+    let run_upgrade = || {
+        let mut builder = InMemoryWasmTestBuilder::default();
+
+        builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+        let mut upgrade_request = {
+            UpgradeRequestBuilder::new()
+                .with_current_protocol_version(PROTOCOL_VERSION)
+                .with_new_protocol_version(new_protocol_version)
+                .with_activation_point(DEFAULT_ACTIVATION_POINT)
+                .build()
+        };
+
+        builder
+            .upgrade_with_upgrade_request(
+                *builder.get_engine_state().config(),
+                &mut upgrade_request,
+            )
+            .expect_upgrade_success();
+        builder
+    };
+
+    let builder = run_upgrade();
+    let snapshot = builder.get_upgrade_snapshot();
+
+    assert_eq!(
+        snapshot,
+        run_upgrade().get_upgrade_snapshot(),
+        "Same upgrade should produce the same snapshot"
+    );
+
+    for contract_hash in [
+        builder.get_mint_contract_hash(),
+        builder.get_auction_contract_hash(),
+        builder.get_handle_payment_contract_hash(),
+        builder.get_standard_payment_contract_hash(),
+    ] {
+        let contract_key = Key::from(contract_hash).to_formatted_string();
+        assert!(
+            snapshot.iter().any(|entry| entry.key == contract_key
+                && entry.transform == casper_types::Transform::WriteContract),
+            "Snapshot should contain the upgraded system contract {}",
+            contract_key
+        );
+    }
+}
+
+#[ignore]
+#[test]
+fn should_apply_major_upgrade_transforms_in_a_stable_order() {
+    let sem_ver = PROTOCOL_VERSION.value();
+    let new_protocol_version =
+        ProtocolVersion::from_parts(sem_ver.major + 1, sem_ver.minor, sem_ver.patch);
+
+    let run_upgrade = || {
+        let mut builder = InMemoryWasmTestBuilder::default();
+
+        builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+        let mut upgrade_request = {
+            UpgradeRequestBuilder::new()
+                .with_current_protocol_version(PROTOCOL_VERSION)
+                .with_new_protocol_version(new_protocol_version)
+                .with_activation_point(DEFAULT_ACTIVATION_POINT)
+                .with_new_validator_slots(DEFAULT_VALIDATOR_SLOTS + 1)
+                .build()
+        };
+
+        builder
+            .upgrade_with_upgrade_request(
+                *builder.get_engine_state().config(),
+                &mut upgrade_request,
+            )
+            .expect_upgrade_success();
+
+        builder
+            .get_upgrade_result(0)
+            .expect("should have upgrade result")
+            .as_ref()
+            .expect("should have upgraded")
+            .applied_transforms()
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+
+    let applied_transforms = run_upgrade();
+
+    assert!(!applied_transforms.is_empty());
+    assert_eq!(
+        applied_transforms,
+        run_upgrade(),
+        "Same upgrade should apply the same transforms in the same order"
+    );
+}
+
+#[ignore]
+#[test]
+fn should_estimate_entries_written_by_major_upgrade() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    let sem_ver = PROTOCOL_VERSION.value();
+    let new_protocol_version =
+        ProtocolVersion::from_parts(sem_ver.major + 1, sem_ver.minor, sem_ver.patch);
+
+    let mut global_state_update = BTreeMap::new();
+    global_state_update.insert(
+        Key::Hash([42; 32]),
+        StoredValue::CLValue(CLValue::from_t(42u64).unwrap()),
+    );
+    let global_state_update_len = global_state_update.len();
+
+    let mut upgrade_request = {
+        UpgradeRequestBuilder::new()
+            .with_current_protocol_version(PROTOCOL_VERSION)
+            .with_new_protocol_version(new_protocol_version)
+            .with_activation_point(DEFAULT_ACTIVATION_POINT)
+            .with_global_state_update(global_state_update)
+            .build()
+    };
+    upgrade_request.with_pre_state_hash(builder.get_post_state_hash().into());
+
+    let estimate = builder
+        .get_engine_state()
+        .estimate_upgrade_cost(CorrelationId::new(), &upgrade_request)
+        .expect("should estimate upgrade cost");
+    assert_eq!(estimate.contracts, 4);
+    assert_eq!(estimate.contract_packages, 4);
+
+    builder
+        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request)
+        .expect_upgrade_success();
+
+    let entries_written = builder
+        .get_upgrade_result(0)
+        .expect("should have upgrade result")
+        .as_ref()
+        .expect("should have upgraded")
+        .execution_effect
+        .transforms
+        .values()
+        .filter(|transform| matches!(transform, Transform::Write(_)))
+        .count();
+    assert_eq!(estimate.writes(global_state_update_len), entries_written);
+}
+
+#[ignore]
+#[test]
+fn should_distinguish_upgrade_effects_reaching_the_same_state() {
+    let sem_ver = PROTOCOL_VERSION.value();
+    let new_protocol_version =
+        ProtocolVersion::from_parts(sem_ver.major, sem_ver.minor, sem_ver.patch + 1);
+
+    let run_upgrade = |rewrite_unbonding_delay: bool| {
+        let mut builder = InMemoryWasmTestBuilder::default();
+
+        builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+        // Rewriting the unbonding delay with its current value leaves the state unchanged.
+        let mut global_state_update = BTreeMap::new();
+        if rewrite_unbonding_delay {
+            let unbonding_delay_key = builder
+                .get_contract(builder.get_auction_contract_hash())
+                .expect("auction should exist")
+                .named_keys()[UNBONDING_DELAY_KEY];
+            global_state_update.insert(
+                unbonding_delay_key,
+                StoredValue::CLValue(CLValue::from_t(DEFAULT_UNBONDING_DELAY).unwrap()),
+            );
+        }
+
+        let mut upgrade_request = {
+            UpgradeRequestBuilder::new()
+                .with_current_protocol_version(PROTOCOL_VERSION)
+                .with_new_protocol_version(new_protocol_version)
+                .with_activation_point(DEFAULT_ACTIVATION_POINT)
+                .with_global_state_update(global_state_update)
+                .build()
+        };
+
+        builder
+            .upgrade_with_upgrade_request(
+                *builder.get_engine_state().config(),
+                &mut upgrade_request,
+            )
+            .expect_upgrade_success();
+
+        builder
+            .get_upgrade_result(0)
+            .expect("should have upgrade result")
+            .as_ref()
+            .expect("should have upgraded")
+            .clone()
+    };
+
+    let plain_upgrade = run_upgrade(false);
+    let rewriting_upgrade = run_upgrade(true);
+
+    assert_eq!(
+        plain_upgrade.post_state_hash,
+        rewriting_upgrade.post_state_hash
+    );
+    assert_ne!(plain_upgrade.effect_hash, rewriting_upgrade.effect_hash);
+}
+
+#[ignore]
+#[test]
+fn should_report_only_changed_config_fields() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    let sem_ver = PROTOCOL_VERSION.value();
+    let new_protocol_version =
+        ProtocolVersion::from_parts(sem_ver.major, sem_ver.minor, sem_ver.patch + 1);
+
+    let upgrade_request = {
+        UpgradeRequestBuilder::new()
+            .with_pre_state_hash(builder.get_post_state_hash())
+            .with_current_protocol_version(PROTOCOL_VERSION)
+            .with_new_protocol_version(new_protocol_version)
+            .with_activation_point(DEFAULT_ACTIVATION_POINT)
+            .with_new_validator_slots(DEFAULT_VALIDATOR_SLOTS + 1)
+            .with_new_auction_delay(DEFAULT_AUCTION_DELAY + 1)
+            .with_new_unbonding_delay(DEFAULT_UNBONDING_DELAY)
+            .build()
+    };
+
+    let changes = builder
+        .get_engine_state()
+        .get_upgrade_config_changes(CorrelationId::new(), &upgrade_request)
+        .expect("should compute config changes");
+
+    assert_eq!(
+        changes,
+        vec![
+            ConfigFieldChange::ValidatorSlots {
+                current: DEFAULT_VALIDATOR_SLOTS,
+                new: DEFAULT_VALIDATOR_SLOTS + 1,
+            },
+            ConfigFieldChange::AuctionDelay {
+                current: DEFAULT_AUCTION_DELAY,
+                new: DEFAULT_AUCTION_DELAY + 1,
+            },
+        ]
+    );
+}
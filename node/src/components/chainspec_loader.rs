@@ -37,7 +37,7 @@ use casper_types::{bytesrepr::FromBytes, EraId, ProtocolVersion, StoredValue};
 #[cfg(test)]
 use crate::utils::RESOURCES_PATH;
 use crate::{
-    components::{contract_runtime::ExecutionPreState, Component},
+    components::{contract_runtime::ExecutionPreState, event_stream_server, Component},
     effect::{
         announcements::ChainspecLoaderAnnouncement,
         requests::{
@@ -301,6 +301,19 @@ impl ChainspecLoader {
         self.after_upgrade
     }
 
+    /// Returns the event announcing the upgrade committed on startup, or `None` if this session
+    /// didn't start with a successful upgrade.
+    pub(crate) fn upgrade_completed_event(&self) -> Option<event_stream_server::Event> {
+        if !self.after_upgrade || self.reactor_exit != Some(ReactorExit::ProcessShouldContinue) {
+            return None;
+        }
+        Some(event_stream_server::Event::UpgradeCompleted {
+            activation_point: self.chainspec.protocol_config.activation_point.era_id(),
+            new_protocol_version: self.chainspec.protocol_config.version,
+            post_state_hash: self.initial_state_root_hash,
+        })
+    }
+
     /// The state root hash with which this session is starting.  It will be the result of running
     /// `ContractRuntime::commit_genesis()` or `ContractRuntime::upgrade()` or else the state root
     /// hash specified in the highest block.
@@ -543,7 +556,7 @@ impl ChainspecLoader {
             })
             .unwrap_or_default();
         Box::new(UpgradeConfig::new(
-            *block.state_root_hash(),
+            (*block.state_root_hash()).into(),
             previous_version,
             new_version,
             Some(self.chainspec.protocol_config.activation_point.era_id()),
@@ -553,6 +566,9 @@ impl ChainspecLoader {
             Some(self.chainspec.core_config.round_seigniorage_rate),
             Some(self.chainspec.core_config.unbonding_delay),
             global_state_update,
+            None,
+            Some(self.chainspec.network_config.name.clone()),
+            None,
         ))
     }
 
@@ -583,12 +599,14 @@ impl ChainspecLoader {
             Ok(UpgradeSuccess {
                 post_state_hash,
                 execution_effect,
+                effect_hash,
+                ..
             }) => {
                 info!("chainspec name {}", self.chainspec.network_config.name);
                 info!("state root hash {}", post_state_hash);
-                trace!(%post_state_hash, ?execution_effect);
+                trace!(%post_state_hash, %effect_hash, ?execution_effect);
                 self.reactor_exit = Some(ReactorExit::ProcessShouldContinue);
-                self.initial_state_root_hash = post_state_hash;
+                self.initial_state_root_hash = post_state_hash.into();
             }
             Err(error) => {
                 error!("failed to upgrade contract runtime: {}", error);
@@ -1193,6 +1211,45 @@ mod tests {
         fixture.assert_initialization_incomplete();
     }
 
+    /// Simulates a successful upgrade, which should be announced on the event stream.
+    #[test]
+    fn should_produce_upgrade_completed_event_after_upgrade() {
+        let mut fixture = TestFixture::new();
+        let mut rng = TestRng::new();
+
+        let previous_era = fixture.current_activation_point() - 1;
+        let height = rng.gen();
+        let earlier_version = fixture.earlier_protocol_version();
+        let highest_block =
+            Block::random_with_specifics(&mut rng, previous_era, height, earlier_version, true);
+        fixture.assert_handle_initialize(Some(highest_block), 1);
+        assert!(fixture.chainspec_loader.upgrade_completed_event().is_none());
+
+        let post_state_hash = Digest::hash(b"post_state_hash");
+        fixture
+            .chainspec_loader
+            .handle_upgrade_result(Ok(UpgradeSuccess {
+                post_state_hash: post_state_hash.into(),
+                execution_effect: Default::default(),
+                effect_hash: Digest::default(),
+                execution_journal: Default::default(),
+            }));
+        fixture.assert_process_should_continue();
+
+        match fixture.chainspec_loader.upgrade_completed_event() {
+            Some(event_stream_server::Event::UpgradeCompleted {
+                activation_point,
+                new_protocol_version,
+                post_state_hash: event_post_state_hash,
+            }) => {
+                assert_eq!(activation_point, fixture.current_activation_point());
+                assert_eq!(new_protocol_version, fixture.current_protocol_version());
+                assert_eq!(event_post_state_hash, post_state_hash);
+            }
+            other => panic!("expected upgrade completed event, got {:?}", other),
+        }
+    }
+
     /// Simulates an invalid run where the highest block is from the previous era, but isn't the
     /// switch block.
     ///
@@ -19,7 +19,7 @@ use datasize::DataSize;
 use lmdb::DatabaseFlags;
 use prometheus::Registry;
 use serde::Serialize;
-use tracing::{debug, info, trace};
+use tracing::{debug, info, trace, warn};
 
 use casper_execution_engine::{
     core::engine_state::{
@@ -407,6 +407,7 @@ impl ContractRuntime {
         system_config: SystemConfig,
         max_associated_keys: u32,
         max_runtime_call_stack_height: u32,
+        network_name: &str,
         registry: &Registry,
     ) -> Result<Self, ConfigError> {
         // TODO: This is bogus, get rid of this
@@ -439,7 +440,11 @@ impl ContractRuntime {
             system_config,
         );
 
-        let engine_state = Arc::new(EngineState::new(global_state, engine_config));
+        let engine_state = Arc::new(
+            EngineState::new(global_state, engine_config)
+                .with_wasm_module_cache_size(contract_runtime_config.max_wasm_module_cache_size())
+                .with_network_name(network_name),
+        );
 
         let metrics = Arc::new(Metrics::new(registry)?);
 
@@ -475,13 +480,24 @@ impl ContractRuntime {
         upgrade_config: UpgradeConfig,
     ) -> Result<UpgradeSuccess, engine_state::Error> {
         debug!(?upgrade_config, "upgrade");
+        match upgrade_config.fingerprint() {
+            Ok(fingerprint) => info!(%fingerprint, "upgrade config fingerprint"),
+            Err(error) => warn!(%error, "failed to fingerprint upgrade config"),
+        }
+        let global_state_update_len = upgrade_config.global_state_update().len();
         let start = Instant::now();
-        let result = self
-            .engine_state
-            .commit_upgrade(CorrelationId::new(), upgrade_config);
+        let result = upgrade_config
+            .validate()
+            .map_err(engine_state::Error::ProtocolUpgrade)
+            .and_then(|validated_upgrade_config| {
+                self.engine_state
+                    .commit_upgrade(CorrelationId::new(), validated_upgrade_config)
+            });
         self.metrics
             .commit_upgrade
             .observe(start.elapsed().as_secs_f64());
+        self.metrics
+            .record_upgrade(global_state_update_len, &result);
         debug!(?result, "upgrade result");
         result
     }
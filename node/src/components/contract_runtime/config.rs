@@ -1,7 +1,7 @@
 use datasize::DataSize;
 use serde::{Deserialize, Serialize};
 
-use casper_execution_engine::shared::utils;
+use casper_execution_engine::shared::{utils, wasm_module_cache::DEFAULT_WASM_MODULE_CACHE_SIZE};
 
 const DEFAULT_MAX_GLOBAL_STATE_SIZE: usize = 805_306_368_000; // 750 GiB
 const DEFAULT_MAX_READERS: u32 = 512;
@@ -31,6 +31,10 @@ pub struct Config {
     ///
     /// Defaults to `false`.
     enable_manual_sync: Option<bool>,
+    /// The maximum number of stored contracts' Wasm modules to keep parsed in memory.
+    ///
+    /// Defaults to 256.  Setting it to 0 disables the cache.
+    max_wasm_module_cache_size: Option<usize>,
 }
 
 impl Config {
@@ -54,6 +58,11 @@ impl Config {
         self.enable_manual_sync
             .unwrap_or(DEFAULT_MANUAL_SYNC_ENABLED)
     }
+
+    pub(crate) fn max_wasm_module_cache_size(&self) -> usize {
+        self.max_wasm_module_cache_size
+            .unwrap_or(DEFAULT_WASM_MODULE_CACHE_SIZE)
+    }
 }
 
 impl Default for Config {
@@ -63,6 +72,7 @@ impl Default for Config {
             max_readers: Some(DEFAULT_MAX_READERS),
             max_query_depth: Some(DEFAULT_MAX_QUERY_DEPTH),
             enable_manual_sync: Some(DEFAULT_MANUAL_SYNC_ENABLED),
+            max_wasm_module_cache_size: Some(DEFAULT_WASM_MODULE_CACHE_SIZE),
         }
     }
 }
@@ -1,4 +1,10 @@
-use prometheus::{self, Gauge, Histogram, IntGauge, Registry};
+use prometheus::{self, Gauge, Histogram, IntCounter, IntCounterVec, IntGauge, Opts, Registry};
+
+use casper_execution_engine::{
+    core::engine_state::{self, upgrade::ProtocolUpgradeError, UpgradeSuccess},
+    shared::{transform::Transform, wasm_module_cache::WasmModuleCache},
+};
+use casper_types::StoredValue;
 
 use crate::{unregister_metric, utils};
 
@@ -23,6 +29,17 @@ const APPLY_EFFECT_HELP: &str = "time in seconds to commit the execution effects
 const COMMIT_UPGRADE_NAME: &str = "contract_runtime_commit_upgrade";
 const COMMIT_UPGRADE_HELP: &str = "time in seconds to commit an upgrade";
 
+const UPGRADE_CONTRACTS_WRITTEN_NAME: &str = "contract_runtime_upgrade_contracts_written";
+const UPGRADE_CONTRACTS_WRITTEN_HELP: &str = "number of contracts written by protocol upgrades";
+
+const UPGRADE_ENTRIES_WRITTEN_NAME: &str = "contract_runtime_upgrade_global_state_entries_written";
+const UPGRADE_ENTRIES_WRITTEN_HELP: &str =
+    "number of global state update entries written by protocol upgrades";
+
+const UPGRADE_FAILURES_NAME: &str = "contract_runtime_upgrade_failures";
+const UPGRADE_FAILURES_HELP: &str = "number of failed protocol upgrades, labeled by error";
+const UPGRADE_FAILURES_LABEL: &str = "error";
+
 const RUN_QUERY_NAME: &str = "contract_runtime_run_query";
 const RUN_QUERY_HELP: &str = "time in seconds to run a query in global state";
 
@@ -61,12 +78,27 @@ const EXEC_BLOCK_HELP: &str = "time in seconds to execute all deploys in a block
 const LATEST_COMMIT_STEP_NAME: &str = "contract_runtime_latest_commit_step";
 const LATEST_COMMIT_STEP_HELP: &str = "duration in seconds of latest commit step at era end";
 
+const WASM_MODULE_CACHE_SIZE_NAME: &str = "contract_runtime_wasm_module_cache_size";
+const WASM_MODULE_CACHE_SIZE_HELP: &str =
+    "number of stored contract wasm modules held in the cache";
+
+const WASM_MODULE_CACHE_HITS_NAME: &str = "contract_runtime_wasm_module_cache_hits";
+const WASM_MODULE_CACHE_HITS_HELP: &str =
+    "number of stored contract wasm modules served from the cache";
+
+const WASM_MODULE_CACHE_MISSES_NAME: &str = "contract_runtime_wasm_module_cache_misses";
+const WASM_MODULE_CACHE_MISSES_HELP: &str =
+    "number of stored contract wasm modules which had to be parsed";
+
 /// Metrics for the contract runtime component.
 #[derive(Debug)]
 pub struct Metrics {
     pub(super) run_execute: Histogram,
     pub(super) apply_effect: Histogram,
     pub(super) commit_upgrade: Histogram,
+    upgrade_contracts_written: IntCounter,
+    upgrade_entries_written: IntCounter,
+    upgrade_failures: IntCounterVec,
     pub(super) run_query: Histogram,
     pub(super) commit_step: Histogram,
     pub(super) get_balance: Histogram,
@@ -79,6 +111,9 @@ pub struct Metrics {
     pub(super) chain_height: IntGauge,
     pub(super) exec_block: Histogram,
     pub(super) latest_commit_step: Gauge,
+    wasm_module_cache_size: IntGauge,
+    wasm_module_cache_hits: IntCounter,
+    wasm_module_cache_misses: IntCounter,
     registry: Registry,
 }
 
@@ -97,6 +132,34 @@ impl Metrics {
         let latest_commit_step = Gauge::new(LATEST_COMMIT_STEP_NAME, LATEST_COMMIT_STEP_HELP)?;
         registry.register(Box::new(latest_commit_step.clone()))?;
 
+        let upgrade_contracts_written = IntCounter::new(
+            UPGRADE_CONTRACTS_WRITTEN_NAME,
+            UPGRADE_CONTRACTS_WRITTEN_HELP,
+        )?;
+        registry.register(Box::new(upgrade_contracts_written.clone()))?;
+
+        let upgrade_entries_written =
+            IntCounter::new(UPGRADE_ENTRIES_WRITTEN_NAME, UPGRADE_ENTRIES_WRITTEN_HELP)?;
+        registry.register(Box::new(upgrade_entries_written.clone()))?;
+
+        let upgrade_failures = IntCounterVec::new(
+            Opts::new(UPGRADE_FAILURES_NAME, UPGRADE_FAILURES_HELP),
+            &[UPGRADE_FAILURES_LABEL],
+        )?;
+        registry.register(Box::new(upgrade_failures.clone()))?;
+
+        let wasm_module_cache_size =
+            IntGauge::new(WASM_MODULE_CACHE_SIZE_NAME, WASM_MODULE_CACHE_SIZE_HELP)?;
+        registry.register(Box::new(wasm_module_cache_size.clone()))?;
+
+        let wasm_module_cache_hits =
+            IntCounter::new(WASM_MODULE_CACHE_HITS_NAME, WASM_MODULE_CACHE_HITS_HELP)?;
+        registry.register(Box::new(wasm_module_cache_hits.clone()))?;
+
+        let wasm_module_cache_misses =
+            IntCounter::new(WASM_MODULE_CACHE_MISSES_NAME, WASM_MODULE_CACHE_MISSES_HELP)?;
+        registry.register(Box::new(wasm_module_cache_misses.clone()))?;
+
         Ok(Metrics {
             run_execute: utils::register_histogram_metric(
                 registry,
@@ -128,6 +191,9 @@ impl Metrics {
                 COMMIT_UPGRADE_HELP,
                 common_buckets.clone(),
             )?,
+            upgrade_contracts_written,
+            upgrade_entries_written,
+            upgrade_failures,
             get_balance: utils::register_histogram_metric(
                 registry,
                 GET_BALANCE_NAME,
@@ -178,9 +244,104 @@ impl Metrics {
                 common_buckets,
             )?,
             latest_commit_step,
+            wasm_module_cache_size,
+            wasm_module_cache_hits,
+            wasm_module_cache_misses,
             registry: registry.clone(),
         })
     }
+
+    /// Updates the upgrade counters with the outcome of a protocol upgrade which carried
+    /// `global_state_update_len` global state update entries.
+    pub(super) fn record_upgrade(
+        &self,
+        global_state_update_len: usize,
+        result: &Result<UpgradeSuccess, engine_state::Error>,
+    ) {
+        match result {
+            Ok(upgrade_success) => {
+                let contracts_written = upgrade_success
+                    .execution_effect
+                    .transforms
+                    .values()
+                    .filter(|transform| {
+                        matches!(transform, Transform::Write(StoredValue::Contract(_)))
+                    })
+                    .count();
+                self.upgrade_contracts_written
+                    .inc_by(contracts_written as u64);
+                self.upgrade_entries_written
+                    .inc_by(global_state_update_len as u64);
+            }
+            Err(error) => self
+                .upgrade_failures
+                .with_label_values(&[upgrade_error_label(error)])
+                .inc(),
+        }
+    }
+
+    /// Updates the wasm module cache gauges from the current state of the cache.
+    pub(super) fn record_wasm_module_cache(&self, wasm_module_cache: &WasmModuleCache) {
+        self.wasm_module_cache_size
+            .set(wasm_module_cache.len() as i64);
+        // The cache keeps running totals, so only the increase since the last call is added.
+        self.wasm_module_cache_hits.inc_by(
+            wasm_module_cache
+                .hits()
+                .saturating_sub(self.wasm_module_cache_hits.get()),
+        );
+        self.wasm_module_cache_misses.inc_by(
+            wasm_module_cache
+                .misses()
+                .saturating_sub(self.wasm_module_cache_misses.get()),
+        );
+    }
+}
+
+/// Returns the label under which a failed upgrade is counted: the `ProtocolUpgradeError` variant,
+/// or "other" for errors raised outside the upgrade itself.
+fn upgrade_error_label(error: &engine_state::Error) -> &'static str {
+    match error {
+        engine_state::Error::ProtocolUpgrade(error) => match error {
+            ProtocolUpgradeError::InvalidUpgradeConfig => "invalid_upgrade_config",
+            ProtocolUpgradeError::MissingSystemContractRegistry => {
+                "missing_system_contract_registry"
+            }
+            ProtocolUpgradeError::UnableToRetrieveSystemContractRegistry(_) => {
+                "unable_to_retrieve_system_contract_registry"
+            }
+            ProtocolUpgradeError::UnableToRetrieveSystemContract(_) => {
+                "unable_to_retrieve_system_contract"
+            }
+            ProtocolUpgradeError::UnableToRetrieveSystemContractPackage(_) => {
+                "unable_to_retrieve_system_contract_package"
+            }
+            ProtocolUpgradeError::FailedToDisablePreviousVersion(_) => {
+                "failed_to_disable_previous_version"
+            }
+            ProtocolUpgradeError::Bytesrepr(_) => "bytesrepr",
+            ProtocolUpgradeError::FailedToCreateSystemRegistry => {
+                "failed_to_create_system_registry"
+            }
+            ProtocolUpgradeError::FailedMigration(_) => "failed_migration",
+            ProtocolUpgradeError::EmptyEntryPoints(_) => "empty_entry_points",
+            ProtocolUpgradeError::NetworkMismatch { .. } => "network_mismatch",
+            ProtocolUpgradeError::UnknownConfigFlag(_) => "unknown_config_flag",
+            ProtocolUpgradeError::NoEnabledVersions(_) => "no_enabled_versions",
+            ProtocolUpgradeError::UnsupportedUpgradePath { .. } => "unsupported_upgrade_path",
+            ProtocolUpgradeError::PackageHashMismatch { .. } => "package_hash_mismatch",
+            ProtocolUpgradeError::UpdateTargetsSystemContract(_) => {
+                "update_targets_system_contract"
+            }
+            ProtocolUpgradeError::MissingRequiredEntryPoint { .. } => {
+                "missing_required_entry_point"
+            }
+            ProtocolUpgradeError::StoredVersionNewerThanTarget { .. } => {
+                "stored_version_newer_than_target"
+            }
+        },
+        _ => "other",
+    }
 }
 
 impl Drop for Metrics {
@@ -188,6 +349,9 @@ impl Drop for Metrics {
         unregister_metric!(self.registry, self.run_execute);
         unregister_metric!(self.registry, self.apply_effect);
         unregister_metric!(self.registry, self.commit_upgrade);
+        unregister_metric!(self.registry, self.upgrade_contracts_written);
+        unregister_metric!(self.registry, self.upgrade_entries_written);
+        unregister_metric!(self.registry, self.upgrade_failures);
         unregister_metric!(self.registry, self.run_query);
         unregister_metric!(self.registry, self.commit_step);
         unregister_metric!(self.registry, self.get_balance);
@@ -200,5 +364,72 @@ impl Drop for Metrics {
         unregister_metric!(self.registry, self.chain_height);
         unregister_metric!(self.registry, self.exec_block);
         unregister_metric!(self.registry, self.latest_commit_step);
+        unregister_metric!(self.registry, self.wasm_module_cache_size);
+        unregister_metric!(self.registry, self.wasm_module_cache_hits);
+        unregister_metric!(self.registry, self.wasm_module_cache_misses);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_execution_engine::{
+        core::engine_state::execution_effect::ExecutionEffect,
+        shared::execution_journal::ExecutionJournal,
+    };
+    use casper_hashing::Digest;
+    use casper_types::{
+        contracts::NamedKeys, CLValue, Contract, ContractPackageHash, ContractWasmHash,
+        EntryPoints, Key, ProtocolVersion,
+    };
+
+    use super::*;
+
+    #[test]
+    fn should_count_upgrade_outcomes() {
+        let registry = Registry::new();
+        let metrics = Metrics::new(&registry).expect("should create metrics");
+
+        let contract = Contract::new(
+            ContractPackageHash::new([2; 32]),
+            ContractWasmHash::new([3; 32]),
+            NamedKeys::new(),
+            EntryPoints::new(),
+            ProtocolVersion::V1_0_0,
+        );
+        let mut execution_effect = ExecutionEffect::default();
+        execution_effect.transforms.insert(
+            Key::Hash([1; 32]),
+            Transform::Write(StoredValue::Contract(contract)),
+        );
+        execution_effect.transforms.insert(
+            Key::Hash([4; 32]),
+            Transform::Write(StoredValue::CLValue(CLValue::unit())),
+        );
+        let upgrade_success = UpgradeSuccess {
+            post_state_hash: Digest::hash(b"post_state_hash").into(),
+            execution_effect,
+            effect_hash: Digest::default(),
+            execution_journal: ExecutionJournal::default(),
+        };
+
+        metrics.record_upgrade(2, &Ok(upgrade_success));
+        assert_eq!(metrics.upgrade_contracts_written.get(), 1);
+        assert_eq!(metrics.upgrade_entries_written.get(), 2);
+
+        metrics.record_upgrade(
+            3,
+            &Err(engine_state::Error::ProtocolUpgrade(
+                ProtocolUpgradeError::InvalidUpgradeConfig,
+            )),
+        );
+        assert_eq!(metrics.upgrade_contracts_written.get(), 1);
+        assert_eq!(metrics.upgrade_entries_written.get(), 2);
+        assert_eq!(
+            metrics
+                .upgrade_failures
+                .with_label_values(&["invalid_upgrade_config"])
+                .get(),
+            1
+        );
     }
 }
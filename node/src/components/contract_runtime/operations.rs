@@ -94,6 +94,7 @@ pub fn execute_finalized_block(
 
     if let Some(metrics) = metrics.as_ref() {
         metrics.exec_block.observe(start.elapsed().as_secs_f64());
+        metrics.record_wasm_module_cache(engine_state.wasm_module_cache());
     }
 
     // If the finalized block has an era report, run the auction contract and get the upcoming era
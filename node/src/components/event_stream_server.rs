@@ -216,6 +216,15 @@ where
                 era_id,
                 execution_effect,
             }),
+            Event::UpgradeCompleted {
+                activation_point,
+                new_protocol_version,
+                post_state_hash,
+            } => self.broadcast(SseData::UpgradeCompleted {
+                activation_point,
+                new_protocol_version,
+                post_state_hash,
+            }),
         }
     }
 }
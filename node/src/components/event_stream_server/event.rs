@@ -1,6 +1,7 @@
 use std::fmt::{self, Display, Formatter};
 
-use casper_types::{EraId, ExecutionEffect, ExecutionResult, PublicKey};
+use casper_hashing::Digest;
+use casper_types::{EraId, ExecutionEffect, ExecutionResult, ProtocolVersion, PublicKey};
 use itertools::Itertools;
 
 use crate::types::{Block, BlockHash, DeployHash, DeployHeader, FinalitySignature, Timestamp};
@@ -26,6 +27,11 @@ pub enum Event {
         era_id: EraId,
         execution_effect: ExecutionEffect,
     },
+    UpgradeCompleted {
+        activation_point: EraId,
+        new_protocol_version: ProtocolVersion,
+        post_state_hash: Digest,
+    },
 }
 
 impl Display for Event {
@@ -56,6 +62,10 @@ impl Display for Event {
             ),
             Event::FinalitySignature(fs) => write!(formatter, "finality signature {}", fs),
             Event::Step { era_id, .. } => write!(formatter, "step committed for {}", era_id),
+            Event::UpgradeCompleted {
+                new_protocol_version,
+                ..
+            } => write!(formatter, "upgrade to {} completed", new_protocol_version),
         }
     }
 }
@@ -34,6 +34,7 @@ use warp::{
     Filter, Reply,
 };
 
+use casper_hashing::Digest;
 use casper_types::{EraId, ExecutionEffect, ExecutionResult, ProtocolVersion, PublicKey};
 
 use super::DeployGetter;
@@ -56,12 +57,13 @@ pub const SSE_API_SIGNATURES_PATH: &str = "sigs";
 pub const QUERY_FIELD: &str = "start_from";
 
 /// The filter associated with `/events/main` path.
-const MAIN_FILTER: [EventFilter; 5] = [
+const MAIN_FILTER: [EventFilter; 6] = [
     EventFilter::BlockAdded,
     EventFilter::DeployProcessed,
     EventFilter::DeployExpired,
     EventFilter::Fault,
     EventFilter::Step,
+    EventFilter::UpgradeCompleted,
 ];
 /// The filter associated with `/events/deploys` path.
 const DEPLOYS_FILTER: [EventFilter; 1] = [EventFilter::DeployAccepted];
@@ -117,6 +119,13 @@ pub enum SseData {
         #[data_size(skip)]
         execution_effect: ExecutionEffect,
     },
+    /// A protocol upgrade has been committed to global state.
+    UpgradeCompleted {
+        activation_point: EraId,
+        #[data_size(skip)]
+        new_protocol_version: ProtocolVersion,
+        post_state_hash: Digest,
+    },
 }
 
 impl SseData {
@@ -130,6 +139,7 @@ impl SseData {
             SseData::Fault { .. } => filter.contains(&EventFilter::Fault),
             SseData::FinalitySignature(_) => filter.contains(&EventFilter::FinalitySignature),
             SseData::Step { .. } => filter.contains(&EventFilter::Step),
+            SseData::UpgradeCompleted { .. } => filter.contains(&EventFilter::UpgradeCompleted),
         }
     }
 }
@@ -274,6 +284,7 @@ pub(super) enum EventFilter {
     Fault,
     FinalitySignature,
     Step,
+    UpgradeCompleted,
 }
 
 /// Filters the `event`, mapping it to a warp event, or `None` if it should be filtered out.
@@ -316,6 +327,7 @@ async fn filter_map_server_sent_event(
         | &SseData::DeployExpired { .. }
         | &SseData::Fault { .. }
         | &SseData::Step { .. }
+        | &SseData::UpgradeCompleted { .. }
         | &SseData::FinalitySignature(_) => Some(Ok(WarpServerSentEvent::default()
             .json_data(&event.data)
             .unwrap_or_else(|error| {
@@ -208,6 +208,7 @@ impl reactor::Reactor for Reactor {
             SystemConfig::default(),
             MAX_ASSOCIATED_KEYS,
             DEFAULT_MAX_RUNTIME_CALL_STACK_HEIGHT,
+            "test",
             registry,
         )
         .unwrap();
@@ -228,6 +228,7 @@ impl Reactor {
                 .chainspec()
                 .core_config
                 .max_runtime_call_stack_height,
+            &chainspec_loader.chainspec().network_config.name,
             registry,
         )?;
 
@@ -464,6 +464,14 @@ impl reactor::Reactor for Reactor {
             DeployGetter::new(effect_builder),
         )?;
 
+        if let Some(upgrade_completed) = chainspec_loader.upgrade_completed_event() {
+            effects.extend(
+                effect_builder
+                    .immediately()
+                    .event(move |_| JoinerEvent::EventStreamServer(upgrade_completed)),
+            );
+        }
+
         let block_validator = BlockValidator::new(Arc::clone(chainspec_loader.chainspec()));
 
         let deploy_fetcher = Fetcher::new("deploy", config.fetcher, registry)?;